@@ -1,15 +1,21 @@
+use std::time::Duration;
+
 use bevy::pbr::StandardMaterial;
 use bevy::prelude::{Handle, Image, Mesh, Resource, TextureAtlas};
 use bevy::utils::HashMap;
 
-use crate::prelude::{CropId, PropId};
+use crate::prelude::{CropId, EdgeStructureId, ItemId, PropId, ResourceId, Season};
 
 /// An object which can be placed on tilled soil, and will grow over time.
 pub struct CropDefinition {
     pub id: CropId,
     pub name: String,
     pub stages: u8,
-    pub growth_time_per_stage: u32,
+    pub growth_time_per_stage: Duration,
+    pub sell_price: u32,
+    pub seed_price: u32,
+    /// Seasons in which this crop is usually harvested. Markets pay less during these.
+    pub seasons: Vec<Season>,
     pub texture_atlas: Handle<TextureAtlas>,
     pub harvested_sprite: Handle<Image>,
 }
@@ -20,10 +26,90 @@ pub struct PropDefinition {
     pub name: String,
     pub mesh: Handle<Mesh>,
     pub material: Handle<StandardMaterial>,
+    pub height: f32,
+    /// Whether actors are unable to walk through this prop.
+    pub is_solid: bool,
+    pub is_shipping_bin: bool,
+    pub build_cost: BuildCost,
+}
+
+/// A raw material used to build things.
+pub struct ResourceDefinition {
+    pub id: ResourceId,
+    pub name: String,
+    pub price: u32,
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+}
+
+/// The resources which need to be paid in order to build something.
+#[derive(Clone, Default)]
+pub struct BuildCost {
+    pub resources: Vec<(ResourceId, u32)>,
+}
+
+impl BuildCost {
+    pub fn new(resources: Vec<(ResourceId, u32)>) -> Self {
+        Self { resources }
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = (ItemId, u32)> + '_ {
+        self.resources.iter().map(|(resource_id, amount)| {
+            (
+                ItemId::Resource {
+                    resource_id: *resource_id,
+                },
+                *amount,
+            )
+        })
+    }
+
+    /// The resources given back when the built thing is torn down again, rounded up so cheap things refund something too.
+    pub fn refund(&self, fraction: f32) -> impl Iterator<Item = (ItemId, u32)> + '_ {
+        self.items()
+            .map(move |(item_id, amount)| {
+                let refund = (amount as f32 * fraction).ceil() as u32;
+                (item_id, refund.min(amount))
+            })
+            .filter(|(_, amount)| *amount > 0)
+    }
+}
+
+/// Something which can be built on the edge between two tiles, like walls, fences or hedges.
+pub struct EdgeStructureDefinition {
+    pub id: EdgeStructureId,
+    pub name: String,
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+    pub height: f32,
+    pub thickness: f32,
+    /// Whether actors are unable to walk through this structure.
+    pub blocks_movement: bool,
+    /// Whether this structure hides what's behind it, so it needs to be faded out when it is in front of the camera.
+    pub blocks_sight: bool,
+    /// Whether areas surrounded by this structure count as rooms and get a roof.
+    pub encloses_rooms: bool,
+    /// Placed once on every tile corner touched by this structure, so adjacent edges share it.
+    pub post: Option<EdgePostDefinition>,
+    pub build_cost: BuildCost,
+}
+
+/// A post standing on tile corners, connecting the edges next to it.
+pub struct EdgePostDefinition {
+    pub mesh: Handle<Mesh>,
+    pub height: f32,
+}
+
+/// Doors only come in a single kind, so they aren't part of the edge structures.
+pub struct DoorDefinition {
+    pub build_cost: BuildCost,
 }
 
 #[derive(Resource)]
 pub struct AllItems {
     pub crops: HashMap<CropId, CropDefinition>,
     pub props: HashMap<PropId, PropDefinition>,
+    pub edge_structures: HashMap<EdgeStructureId, EdgeStructureDefinition>,
+    pub resources: HashMap<ResourceId, ResourceDefinition>,
+    pub door: DoorDefinition,
 }
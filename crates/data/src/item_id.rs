@@ -1,6 +1,6 @@
 use std::fmt::{Display, Formatter};
 
-use crate::prelude::AllItems;
+use crate::prelude::{AllItems, ToolTier};
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct CropId(pub u32);
@@ -8,6 +8,12 @@ pub struct CropId(pub u32);
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct PropId(pub u32);
 
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct EdgeStructureId(pub u32);
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ResourceId(pub u32);
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum ToolId {
     Hoe,
@@ -29,8 +35,9 @@ impl Display for ToolId {
 pub enum ItemId {
     Crop { crop_id: CropId },
     Seed { crop_id: CropId },
-    Tool { tool_id: ToolId },
+    Tool { tool_id: ToolId, tier: ToolTier },
     Prop { prop_id: PropId },
+    Resource { resource_id: ResourceId },
 }
 
 impl Display for ItemId {
@@ -39,7 +46,8 @@ impl Display for ItemId {
             ItemId::Crop { crop_id } => write!(f, "Crop (ID {})", crop_id.0),
             ItemId::Seed { crop_id } => write!(f, "Seed (ID {})", crop_id.0),
             ItemId::Prop { prop_id: object_id } => write!(f, "Prop(ID {})", object_id.0),
-            ItemId::Tool { tool_id } => tool_id.fmt(f),
+            ItemId::Tool { tool_id, tier } => write!(f, "{} {}", tier, tool_id),
+            ItemId::Resource { resource_id } => write!(f, "Resource (ID {})", resource_id.0),
         }
     }
 }
@@ -51,8 +59,9 @@ impl ItemId {
             ItemId::Seed { crop_id } => {
                 format!("{} Seed", all_items.crops[crop_id].name.clone())
             }
-            ItemId::Tool { tool_id } => tool_id.to_string(),
+            ItemId::Tool { tool_id, tier } => format!("{} {}", tier, tool_id),
             ItemId::Prop { prop_id: object_id } => all_items.props[object_id].name.clone(),
+            ItemId::Resource { resource_id } => all_items.resources[resource_id].name.clone(),
         }
     }
 }
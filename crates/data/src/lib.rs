@@ -1,6 +1,8 @@
 mod item_definitions;
 mod item_id;
+mod season;
+mod tool;
 
 pub mod prelude {
-    pub use {crate::item_definitions::*, crate::item_id::*};
+    pub use {crate::item_definitions::*, crate::item_id::*, crate::season::*, crate::tool::*};
 }
@@ -0,0 +1,67 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+pub enum ToolTier {
+    Basic,
+    Copper,
+    Iron,
+}
+
+impl Display for ToolTier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToolTier::Basic => write!(f, "Basic"),
+            ToolTier::Copper => write!(f, "Copper"),
+            ToolTier::Iron => write!(f, "Iron"),
+        }
+    }
+}
+
+impl ToolTier {
+    /// The tier a tool of this tier can be upgraded to, if any.
+    pub fn next(&self) -> Option<ToolTier> {
+        match self {
+            ToolTier::Basic => Some(ToolTier::Copper),
+            ToolTier::Copper => Some(ToolTier::Iron),
+            ToolTier::Iron => None,
+        }
+    }
+
+    /// How many gold it costs to upgrade a tool to this tier.
+    pub fn upgrade_price(&self) -> u32 {
+        match self {
+            ToolTier::Basic => 0,
+            ToolTier::Copper => 100,
+            ToolTier::Iron => 250,
+        }
+    }
+
+    /// How many times a tool of this tier can be charged up before it stops growing.
+    pub fn max_charge_level(&self) -> u32 {
+        match self {
+            ToolTier::Basic => 0,
+            ToolTier::Copper => 1,
+            ToolTier::Iron => 2,
+        }
+    }
+}
+
+/// The tiles affected by a single use of a tool, relative to the targeted tile.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum AreaPattern {
+    Single,
+    /// Three tiles in a row, starting at the target and following the direction the user is facing.
+    Line3,
+    /// A 3x3 square around the target.
+    Square3,
+}
+
+impl AreaPattern {
+    pub fn from_charge_level(charge_level: u32) -> Self {
+        match charge_level {
+            0 => AreaPattern::Single,
+            1 => AreaPattern::Line3,
+            _ => AreaPattern::Square3,
+        }
+    }
+}
@@ -2,20 +2,62 @@ use std::fmt::{Debug, Display, Formatter};
 
 use bevy::prelude::Resource;
 
-use data::prelude::ItemId;
+use data::prelude::{EdgeStructureId, ItemId, ToolId};
+
+use crate::game::blueprints::MAX_BLUEPRINT_SIZE;
 
 #[derive(Resource, Copy, Clone, Debug, Eq, PartialEq)]
 pub enum ActiveTool {
     None,
     Item(ItemId),
-    Wall,
+    Wall(EdgeStructureId),
+    Door,
+    /// Drag out an area to turn it into a blueprint.
+    CopyArea,
+    /// Builds the blueprint which is currently in the clipboard.
+    Blueprint,
+}
+
+impl ActiveTool {
+    /// The maximum width and height of the area which can be selected at once with this tool.
+    pub fn max_area_selection_size(&self) -> u32 {
+        match self {
+            ActiveTool::Item(ItemId::Tool { tool_id, .. }) => match tool_id {
+                ToolId::Hoe => 5,
+                ToolId::Scythe => 5,
+                ToolId::Pickaxe => 1,
+            },
+            ActiveTool::Item(ItemId::Seed { .. }) => 5,
+            ActiveTool::Item(_) => 1,
+            ActiveTool::Wall(_) => 1,
+            ActiveTool::Door => 1,
+            ActiveTool::CopyArea => MAX_BLUEPRINT_SIZE,
+            ActiveTool::Blueprint => 1,
+            ActiveTool::None => 1,
+        }
+    }
+
+    /// How many times this tool can be charged up by holding the interaction button in place.
+    pub fn max_charge_level(&self) -> u32 {
+        match self {
+            ActiveTool::Item(ItemId::Tool { tier, .. }) => tier.max_charge_level(),
+            _ => 0,
+        }
+    }
+
+    pub fn uses_area_selection(&self) -> bool {
+        self.max_area_selection_size() > 1 || self.max_charge_level() > 0
+    }
 }
 
 impl Display for ActiveTool {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ActiveTool::Item(item) => std::fmt::Display::fmt(&item, f),
-            ActiveTool::Wall => write!(f, "Wall"),
+            ActiveTool::Wall(structure_id) => write!(f, "Wall (ID {})", structure_id.0),
+            ActiveTool::Door => write!(f, "Door"),
+            ActiveTool::CopyArea => write!(f, "Copy Area"),
+            ActiveTool::Blueprint => write!(f, "Blueprint"),
             ActiveTool::None => write!(f, "None"),
         }
     }
@@ -0,0 +1,796 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::app::{App, Plugin, Update};
+use bevy::core::Name;
+use bevy::log::{error, info};
+use bevy::math::IVec2;
+use bevy::pbr::PbrBundle;
+use bevy::prelude::{
+    default, in_state, BuildChildren, Commands, Condition, DespawnRecursiveExt, Entity,
+    EventWriter, IntoSystemConfigs, Local, Query, Res, ResMut, Resource, Transform,
+};
+use bevy_egui::egui::{Align2, Pos2};
+use bevy_egui::{egui, EguiContexts};
+use leafwing_input_manager::action_state::ActionState;
+
+use data::prelude::{AllItems, EdgeStructureId, ItemId, PropId, ToolId};
+
+use crate::game::interaction::{BuildingRotation, Reach, TileInteractionEvent};
+use crate::game::walls::{
+    build_and_spawn_door_preview, build_and_spawn_wall_entity_with_mesh_and_material,
+    find_edge_structure,
+};
+use crate::prelude::loaded_chunks::LoadedChunks;
+use crate::prelude::tile_cursor::{CursorInputState, MouseCursorOnTile};
+use crate::prelude::tile_data::EdgeStructure;
+use crate::prelude::{
+    ActiveTool, CardinalDirection, DebugMaterials, DebugMeshes, GameState, Inventory, MapPos,
+    MouseCursorOverUiState, PlayerAction, WorldData,
+};
+
+/// The maximum width and height of the area which can be copied at once.
+pub const MAX_BLUEPRINT_SIZE: u32 = 16;
+
+const BLUEPRINT_DIRECTORY: &str = "blueprints";
+const BLUEPRINT_EXTENSION: &str = "blueprint";
+const FORMAT_VERSION: u32 = 1;
+
+pub struct BlueprintPlugin;
+impl Plugin for BlueprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlueprintClipboard>()
+            .add_systems(
+                Update,
+                (
+                    select_blueprint_tool,
+                    blueprint_ui,
+                    update_blueprint_preview,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (copy_area, paste_blueprint)
+                    .run_if(
+                        in_state(MouseCursorOverUiState::NotOverUI)
+                            .or_else(in_state(CursorInputState::Gamepad)),
+                    )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Walls, props and tilled tiles of a rectangular area, positioned relative to its north western corner.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Blueprint {
+    pub size: IVec2,
+    pub tilled: Vec<IVec2>,
+    pub edges: Vec<(IVec2, CardinalDirection, EdgeStructure)>,
+    pub props: Vec<(IVec2, PropId)>,
+}
+
+/// The blueprint which is built when using [ActiveTool::Blueprint].
+#[derive(Resource, Default)]
+pub struct BlueprintClipboard {
+    pub blueprint: Option<Blueprint>,
+}
+
+impl Blueprint {
+    /// Captures everything inside the rectangle spanned by min and max.
+    pub fn capture(world_data: &WorldData, min: IVec2, max: IVec2) -> Self {
+        let mut blueprint = Blueprint {
+            size: max - min + IVec2::ONE,
+            ..default()
+        };
+
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let global_pos = IVec2::new(x, y);
+                let offset = global_pos - min;
+                let pos = MapPos::from_global_tile_pos(global_pos);
+                let Some(chunk) = world_data.chunks.get(&pos.chunk) else {
+                    continue;
+                };
+
+                let tile = chunk.at_pos(&pos.tile);
+                if tile.is_tilled {
+                    blueprint.tilled.push(offset);
+                }
+
+                // Walls may be stored on either neighbouring tile, so every edge is only looked up once:
+                // the north and west edge of each tile, plus the south and east edge along the border
+                let edges = [
+                    Some(CardinalDirection::North),
+                    Some(CardinalDirection::West),
+                    (y == max.y).then_some(CardinalDirection::South),
+                    (x == max.x).then_some(CardinalDirection::East),
+                ];
+                for edge in edges.into_iter().flatten() {
+                    match find_edge_structure(world_data, pos, edge) {
+                        Some((_, _, EdgeStructure::Wall { structure_id })) => blueprint
+                            .edges
+                            .push((offset, edge, EdgeStructure::Wall { structure_id })),
+                        // Doors always get built closed
+                        Some((_, _, EdgeStructure::Door { .. })) => blueprint.edges.push((
+                            offset,
+                            edge,
+                            EdgeStructure::Door { is_open: false },
+                        )),
+                        None => {}
+                    }
+                }
+
+                if let Some(prop) = chunk.props.get(&pos.tile) {
+                    blueprint.props.push((offset, prop.prop_id));
+                }
+            }
+        }
+
+        blueprint
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tilled.is_empty() && self.edges.is_empty() && self.props.is_empty()
+    }
+
+    /// Returns a copy turned clockwise until its north side faces the given direction.
+    pub fn rotated(&self, direction: CardinalDirection) -> Self {
+        let mut result = self.clone();
+        for _ in 0..direction.quarter_turns_from_north() {
+            let height = result.size.y;
+            let turn = |offset: &IVec2| IVec2::new(height - 1 - offset.y, offset.x);
+
+            result = Blueprint {
+                size: IVec2::new(result.size.y, result.size.x),
+                tilled: result.tilled.iter().map(turn).collect(),
+                edges: result
+                    .edges
+                    .iter()
+                    .map(|(offset, edge, structure)| {
+                        (turn(offset), edge.rotated_clockwise(), *structure)
+                    })
+                    .collect(),
+                props: result
+                    .props
+                    .iter()
+                    .map(|(offset, prop_id)| (turn(offset), *prop_id))
+                    .collect(),
+            };
+        }
+
+        result
+    }
+
+    /// Serializes the blueprint into its line based file format, one element per line.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("blueprint {}", FORMAT_VERSION),
+            format!("size {} {}", self.size.x, self.size.y),
+        ];
+
+        for offset in self.tilled.iter() {
+            lines.push(format!("tilled {} {}", offset.x, offset.y));
+        }
+        for (offset, edge, structure) in self.edges.iter() {
+            lines.push(match structure {
+                EdgeStructure::Wall { structure_id } => format!(
+                    "wall {} {} {} {}",
+                    offset.x,
+                    offset.y,
+                    direction_name(*edge),
+                    structure_id.0
+                ),
+                EdgeStructure::Door { .. } => {
+                    format!("door {} {} {}", offset.x, offset.y, direction_name(*edge))
+                }
+            });
+        }
+        for (offset, prop_id) in self.props.iter() {
+            lines.push(format!("prop {} {} {}", offset.x, offset.y, prop_id.0));
+        }
+
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    /// Parses the format written by [Blueprint::to_text]. Empty lines and lines starting with # are ignored.
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut blueprint = Blueprint::default();
+        let mut has_header = false;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let result = if !has_header {
+                match parts.as_slice() {
+                    ["blueprint", version] => {
+                        has_header = true;
+                        parse_number::<u32>(version).and_then(|version| {
+                            if version == FORMAT_VERSION {
+                                Ok(())
+                            } else {
+                                Err(format!("Unsupported version {}", version))
+                            }
+                        })
+                    }
+                    _ => Err("Expected blueprint header".to_string()),
+                }
+            } else {
+                blueprint.parse_element(&parts)
+            };
+
+            if let Err(e) = result {
+                return Err(format!("Line {}: {}", index + 1, e));
+            }
+        }
+
+        if !has_header {
+            return Err("File is empty".to_string());
+        }
+        if blueprint.size == IVec2::ZERO {
+            return Err("Missing size".to_string());
+        }
+
+        let all_offsets = blueprint
+            .tilled
+            .iter()
+            .chain(blueprint.edges.iter().map(|(offset, _, _)| offset))
+            .chain(blueprint.props.iter().map(|(offset, _)| offset));
+        for offset in all_offsets {
+            if offset.cmplt(IVec2::ZERO).any() || offset.cmpge(blueprint.size).any() {
+                return Err(format!("Position {} lies outside of the blueprint", offset));
+            }
+        }
+
+        Ok(blueprint)
+    }
+
+    fn parse_element(&mut self, parts: &[&str]) -> Result<(), String> {
+        match parts {
+            ["size", x, y] => {
+                self.size = IVec2::new(parse_number(x)?, parse_number(y)?);
+                if self.size.cmplt(IVec2::ONE).any()
+                    || self
+                        .size
+                        .cmpgt(IVec2::splat(MAX_BLUEPRINT_SIZE as i32))
+                        .any()
+                {
+                    return Err(format!("Invalid size {}", self.size));
+                }
+            }
+            ["tilled", x, y] => self.tilled.push(parse_offset(x, y)?),
+            ["wall", x, y, edge, structure_id] => self.edges.push((
+                parse_offset(x, y)?,
+                parse_direction(edge)?,
+                EdgeStructure::Wall {
+                    structure_id: EdgeStructureId(parse_number(structure_id)?),
+                },
+            )),
+            ["door", x, y, edge] => self.edges.push((
+                parse_offset(x, y)?,
+                parse_direction(edge)?,
+                EdgeStructure::Door { is_open: false },
+            )),
+            ["prop", x, y, prop_id] => self
+                .props
+                .push((parse_offset(x, y)?, PropId(parse_number(prop_id)?))),
+            _ => return Err(format!("Unable to parse '{}'", parts.join(" "))),
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", value))
+}
+
+fn parse_offset(x: &str, y: &str) -> Result<IVec2, String> {
+    Ok(IVec2::new(parse_number(x)?, parse_number(y)?))
+}
+
+fn direction_name(direction: CardinalDirection) -> &'static str {
+    match direction {
+        CardinalDirection::North => "north",
+        CardinalDirection::East => "east",
+        CardinalDirection::South => "south",
+        CardinalDirection::West => "west",
+    }
+}
+
+fn parse_direction(value: &str) -> Result<CardinalDirection, String> {
+    CardinalDirection::ALL
+        .into_iter()
+        .find(|direction| direction_name(*direction) == value)
+        .ok_or_else(|| format!("'{}' is not a valid direction", value))
+}
+
+fn blueprint_path(name: &str) -> PathBuf {
+    PathBuf::from(BLUEPRINT_DIRECTORY).join(format!("{}.{}", name, BLUEPRINT_EXTENSION))
+}
+
+fn is_valid_blueprint_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn save_blueprint(name: &str, blueprint: &Blueprint) -> Result<(), String> {
+    fs::create_dir_all(BLUEPRINT_DIRECTORY).map_err(|e| e.to_string())?;
+    fs::write(blueprint_path(name), blueprint.to_text()).map_err(|e| e.to_string())
+}
+
+fn load_blueprint(name: &str) -> Result<Blueprint, String> {
+    let text = fs::read_to_string(blueprint_path(name)).map_err(|e| e.to_string())?;
+    Blueprint::from_text(&text)
+}
+
+/// Returns the names of all saved blueprints, sorted alphabetically.
+fn list_saved_blueprints() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(BLUEPRINT_DIRECTORY) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map_or(false, |e| e == BLUEPRINT_EXTENSION))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Switches between copying areas and building the copied blueprint.
+fn select_blueprint_tool(
+    mut active_tool: ResMut<ActiveTool>,
+    action_state: Query<&ActionState<PlayerAction>>,
+    clipboard: Res<BlueprintClipboard>,
+) {
+    let action_state = action_state.get_single();
+    if action_state.is_err() {
+        error!("PlayerAction State was missing!");
+        return;
+    }
+    let action_state = action_state.unwrap();
+    if !action_state.just_pressed(&PlayerAction::Blueprint) {
+        return;
+    }
+
+    *active_tool = if *active_tool == ActiveTool::CopyArea && clipboard.blueprint.is_some() {
+        ActiveTool::Blueprint
+    } else {
+        ActiveTool::CopyArea
+    };
+}
+
+fn copy_area(
+    mut active_tool: ResMut<ActiveTool>,
+    mut clipboard: ResMut<BlueprintClipboard>,
+    action_state: Query<&ActionState<PlayerAction>>,
+    mouse_cursor: Option<Res<MouseCursorOnTile>>,
+    world_data: Res<WorldData>,
+    mut start: Local<Option<MapPos>>,
+) {
+    if *active_tool != ActiveTool::CopyArea {
+        *start = None;
+        return;
+    }
+
+    let action_state = action_state.get_single();
+    if action_state.is_err() {
+        error!("PlayerAction State was missing!");
+        return;
+    }
+    let action_state = action_state.unwrap();
+    let Some(mouse_cursor) = mouse_cursor else {
+        return;
+    };
+
+    if action_state.just_pressed(&PlayerAction::Cancel) {
+        *start = None;
+        return;
+    }
+
+    if action_state.just_pressed(&PlayerAction::Interact) {
+        *start = Some(mouse_cursor.tile_pos);
+        return;
+    }
+
+    if !action_state.just_released(&PlayerAction::Interact) {
+        return;
+    }
+    let Some(start) = start.take() else {
+        return;
+    };
+
+    // Same clamping as the area selection, so the copied area matches the highlighted tiles
+    let max_offset = MAX_BLUEPRINT_SIZE as i32 - 1;
+    let start = start.global_tile_pos();
+    let end = mouse_cursor.tile_pos.global_tile_pos();
+    let end = start + (end - start).clamp(IVec2::splat(-max_offset), IVec2::splat(max_offset));
+
+    let blueprint = Blueprint::capture(&world_data, start.min(end), start.max(end));
+    if blueprint.is_empty() {
+        info!("Nothing to copy in the selected area.");
+        return;
+    }
+
+    clipboard.blueprint = Some(blueprint);
+    *active_tool = ActiveTool::Blueprint;
+}
+
+/// Turns the blueprint into regular interactions, so building it works and costs the same as building by hand.
+fn paste_blueprint(
+    active_tool: Res<ActiveTool>,
+    clipboard: Res<BlueprintClipboard>,
+    building_rotation: Res<BuildingRotation>,
+    player: Query<(
+        Entity,
+        &ActionState<PlayerAction>,
+        &Transform,
+        &Reach,
+        &Inventory,
+    )>,
+    mouse_cursor: Option<Res<MouseCursorOnTile>>,
+    world_data: Res<WorldData>,
+    mut tile_interaction_events: EventWriter<TileInteractionEvent>,
+) {
+    if *active_tool != ActiveTool::Blueprint {
+        return;
+    }
+    let Some(blueprint) = &clipboard.blueprint else {
+        return;
+    };
+    let Some(mouse_cursor) = mouse_cursor else {
+        return;
+    };
+    let player = player.get_single();
+    if player.is_err() {
+        error!("PlayerAction State was missing!");
+        return;
+    }
+    let (actor, action_state, transform, reach, inventory) = player.unwrap();
+    if !action_state.just_pressed(&PlayerAction::Interact) {
+        return;
+    }
+
+    let origin = mouse_cursor.tile_pos.global_tile_pos();
+    let blueprint = blueprint.rotated(building_rotation.direction);
+    let to_map_pos = |offset: &IVec2| MapPos::from_global_tile_pos(origin + *offset);
+    // Basic tools are always available, so there's always a hoe to till with
+    let hoe = ActiveTool::Item(ItemId::Tool {
+        tool_id: ToolId::Hoe,
+        tier: inventory.best_tool_tier(ToolId::Hoe),
+    });
+
+    // Tiles need to be tilled before anything is put on them
+    let mut interactions: Vec<(MapPos, CardinalDirection, ActiveTool)> = Vec::new();
+    for offset in blueprint.tilled.iter() {
+        interactions.push((to_map_pos(offset), building_rotation.direction, hoe));
+    }
+    for (offset, prop_id) in blueprint.props.iter() {
+        interactions.push((
+            to_map_pos(offset),
+            building_rotation.direction,
+            ActiveTool::Item(ItemId::Prop { prop_id: *prop_id }),
+        ));
+    }
+    for (offset, edge, structure) in blueprint.edges.iter() {
+        let pos = to_map_pos(offset);
+        // Interacting with an existing door would toggle it
+        if find_edge_structure(&world_data, pos, *edge).is_some() {
+            continue;
+        }
+
+        let tool = match structure {
+            EdgeStructure::Wall { structure_id } => ActiveTool::Wall(*structure_id),
+            EdgeStructure::Door { .. } => ActiveTool::Door,
+        };
+        interactions.push((pos, *edge, tool));
+    }
+
+    tile_interaction_events.send_batch(
+        interactions
+            .into_iter()
+            .filter(|(pos, _, _)| reach.contains(transform.translation, pos))
+            .map(|(pos, rotation, used_tool)| TileInteractionEvent {
+                actor,
+                pos,
+                rotation,
+                used_tool,
+                targets_edge: true,
+            }),
+    );
+}
+
+#[derive(Default)]
+struct BlueprintPreview {
+    origin: Option<MapPos>,
+    rotation: Option<CardinalDirection>,
+    blueprint: Option<Blueprint>,
+    entities: Vec<Entity>,
+}
+
+/// Shows a ghost of the blueprint where it would be built.
+fn update_blueprint_preview(
+    mut commands: Commands,
+    active_tool: Res<ActiveTool>,
+    clipboard: Res<BlueprintClipboard>,
+    building_rotation: Res<BuildingRotation>,
+    mouse_cursor: Option<Res<MouseCursorOnTile>>,
+    loaded_chunks: Res<LoadedChunks>,
+    all_items: Res<AllItems>,
+    debug_meshes: Res<DebugMeshes>,
+    debug_materials: Res<DebugMaterials>,
+    mut preview: Local<BlueprintPreview>,
+) {
+    let origin = mouse_cursor.map(|cursor| cursor.tile_pos);
+    let blueprint = match *active_tool {
+        ActiveTool::Blueprint => clipboard.blueprint.clone(),
+        _ => None,
+    };
+
+    if preview.origin == origin
+        && preview.rotation == Some(building_rotation.direction)
+        && preview.blueprint == blueprint
+    {
+        return;
+    }
+
+    for entity in preview.entities.drain(..) {
+        commands.entity(entity).despawn_recursive();
+    }
+    preview.origin = origin;
+    preview.rotation = Some(building_rotation.direction);
+    preview.blueprint = blueprint;
+
+    let (Some(origin), Some(blueprint)) = (origin, &preview.blueprint) else {
+        return;
+    };
+
+    let origin = origin.global_tile_pos();
+    let blueprint = blueprint.rotated(building_rotation.direction);
+    let material = debug_materials.preview_ghost.clone();
+    let mut entities = Vec::new();
+
+    let loaded_tile = |offset: &IVec2| {
+        let pos = MapPos::from_global_tile_pos(origin + *offset);
+        let loaded_data = loaded_chunks.chunks.get(&pos.chunk)?;
+        Some((
+            pos,
+            loaded_data.chunk_parent,
+            loaded_data.get_tile(pos.tile.x, pos.tile.y)?,
+        ))
+    };
+
+    for offset in blueprint.tilled.iter() {
+        let Some((_, _, tile)) = loaded_tile(offset) else {
+            continue;
+        };
+        entities.push(
+            commands
+                .spawn((
+                    Name::new("Tilled Preview"),
+                    PbrBundle {
+                        mesh: debug_meshes.tile.clone(),
+                        material: material.clone(),
+                        transform: Transform::from_xyz(0.0, 0.01, 0.0),
+                        ..default()
+                    },
+                ))
+                .set_parent(tile)
+                .id(),
+        );
+    }
+
+    for (offset, prop_id) in blueprint.props.iter() {
+        let Some((pos, chunk_parent, _)) = loaded_tile(offset) else {
+            continue;
+        };
+        let Some(definition) = all_items.props.get(prop_id) else {
+            continue;
+        };
+        entities.push(
+            commands
+                .spawn((
+                    Name::new(format!("{} Preview", definition.name)),
+                    PbrBundle {
+                        mesh: definition.mesh.clone(),
+                        material: material.clone(),
+                        transform: Transform::from_translation(
+                            pos.pos_inside_chunk(definition.height * 0.5),
+                        ),
+                        ..default()
+                    },
+                ))
+                .set_parent(chunk_parent)
+                .id(),
+        );
+    }
+
+    for (offset, edge, structure) in blueprint.edges.iter() {
+        let Some((_, _, tile)) = loaded_tile(offset) else {
+            continue;
+        };
+        match structure {
+            EdgeStructure::Wall { structure_id } => {
+                let Some(definition) = all_items.edge_structures.get(structure_id) else {
+                    continue;
+                };
+                entities.push(build_and_spawn_wall_entity_with_mesh_and_material(
+                    &mut commands,
+                    tile,
+                    *edge,
+                    definition,
+                    material.clone(),
+                ));
+            }
+            EdgeStructure::Door { .. } => {
+                entities.push(build_and_spawn_door_preview(
+                    &mut commands,
+                    tile,
+                    *edge,
+                    &debug_meshes,
+                    material.clone(),
+                ));
+            }
+        }
+    }
+
+    preview.entities = entities;
+}
+
+#[derive(Default)]
+struct BlueprintUiState {
+    name: String,
+    saved_blueprints: Option<Vec<String>>,
+    status: String,
+}
+
+fn blueprint_ui(
+    mut contexts: EguiContexts,
+    mut active_tool: ResMut<ActiveTool>,
+    mut clipboard: ResMut<BlueprintClipboard>,
+    mut state: Local<BlueprintUiState>,
+) {
+    if !matches!(*active_tool, ActiveTool::CopyArea | ActiveTool::Blueprint) {
+        return;
+    }
+
+    let state = &mut *state;
+    let saved_blueprints = state
+        .saved_blueprints
+        .get_or_insert_with(list_saved_blueprints);
+
+    let mut refresh = false;
+    egui::Window::new("Blueprints")
+        .collapsible(true)
+        .resizable(false)
+        .anchor(Align2::LEFT_BOTTOM, egui::Vec2::new(0.0, 0.0))
+        .fixed_pos(Pos2::new(5.0, 5.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut state.name);
+                let can_save =
+                    clipboard.blueprint.is_some() && is_valid_blueprint_name(&state.name);
+                if ui
+                    .add_enabled(can_save, egui::Button::new("Save"))
+                    .clicked()
+                {
+                    if let Some(blueprint) = &clipboard.blueprint {
+                        state.status = match save_blueprint(&state.name, blueprint) {
+                            Ok(()) => format!("Saved {}", state.name),
+                            Err(e) => format!("Unable to save {}: {}", state.name, e),
+                        };
+                        refresh = true;
+                    }
+                }
+            });
+
+            ui.separator();
+            for name in saved_blueprints.iter() {
+                if ui.button(name).clicked() {
+                    match load_blueprint(name) {
+                        Ok(blueprint) => {
+                            clipboard.blueprint = Some(blueprint);
+                            *active_tool = ActiveTool::Blueprint;
+                            state.name = name.clone();
+                            state.status = format!("Loaded {}", name);
+                        }
+                        Err(e) => state.status = format!("Unable to load {}: {}", name, e),
+                    }
+                }
+            }
+
+            if ui.button("Refresh").clicked() {
+                refresh = true;
+            }
+            if !state.status.is_empty() {
+                ui.label(&state.status);
+            }
+        });
+
+    if refresh {
+        state.saved_blueprints = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_blueprint() -> Blueprint {
+        Blueprint {
+            size: IVec2::new(3, 2),
+            tilled: vec![IVec2::new(0, 0), IVec2::new(2, 1)],
+            edges: vec![
+                (
+                    IVec2::new(0, 0),
+                    CardinalDirection::North,
+                    EdgeStructure::Wall {
+                        structure_id: EdgeStructureId(1),
+                    },
+                ),
+                (
+                    IVec2::new(2, 1),
+                    CardinalDirection::East,
+                    EdgeStructure::Door { is_open: false },
+                ),
+            ],
+            props: vec![(IVec2::new(1, 1), PropId(0))],
+        }
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let blueprint = sample_blueprint();
+        assert_eq!(Blueprint::from_text(&blueprint.to_text()), Ok(blueprint));
+    }
+
+    #[test]
+    fn rejects_positions_outside_of_the_blueprint() {
+        for element in ["tilled 3 0", "tilled 0 2", "prop -1 0 0", "door 0 5 north"] {
+            let text = format!("blueprint 1\nsize 3 2\n{}\n", element);
+            assert!(Blueprint::from_text(&text).is_err(), "{}", element);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        let too_large = MAX_BLUEPRINT_SIZE + 1;
+        let sizes = [
+            "0 1".to_string(),
+            "1 -1".to_string(),
+            format!("{} 1", too_large),
+            format!("1 {}", too_large),
+        ];
+        for size in sizes {
+            let text = format!("blueprint 1\nsize {}\n", size);
+            assert!(Blueprint::from_text(&text).is_err(), "{}", size);
+        }
+        assert!(Blueprint::from_text("blueprint 1\ntilled 0 0\n").is_err());
+    }
+
+    #[test]
+    fn four_quarter_turns_return_the_original() {
+        let blueprint = sample_blueprint();
+        let turned_once = blueprint.rotated(CardinalDirection::East);
+        assert_eq!(turned_once.size, IVec2::new(2, 3));
+        assert_ne!(turned_once, blueprint);
+
+        let mut rotated = blueprint.clone();
+        for _ in 0..4 {
+            rotated = rotated.rotated(CardinalDirection::East);
+        }
+        assert_eq!(rotated, blueprint);
+    }
+}
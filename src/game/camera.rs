@@ -1,3 +1,5 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
 use bevy::ecs::query::QuerySingleError;
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
@@ -12,7 +14,7 @@ use leafwing_input_manager::prelude::UserInput;
 use leafwing_input_manager::user_input::InputKind;
 use leafwing_input_manager::{Actionlike, InputManagerBundle};
 
-use crate::prelude::{GameState, MouseCursorOverUiState, TileRaycastSet};
+use crate::prelude::{GameState, MouseCursorOverUiState, TileRaycastSet, WorldData, CHUNK_SIZE};
 
 const SPEED: f32 = 50.0;
 const SUPERSPEED_MULTIPLIER: f32 = 3.0;
@@ -21,7 +23,9 @@ pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(InputManagerPlugin::<CameraAction>::default())
+        app.init_resource::<CameraRotationSettings>()
+            .add_state::<CameraMode>()
+            .add_plugins(InputManagerPlugin::<CameraAction>::default())
             .add_plugins(CameraControllerPlugin)
             .add_systems(Startup, init)
             .add_systems(
@@ -30,6 +34,11 @@ impl Plugin for CameraPlugin {
                     .run_if(in_state(MouseCursorOverUiState::NotOverUI))
                     .run_if(in_state(GameState::Playing)),
             )
+            .add_systems(
+                Update,
+                (cycle_camera_mode, toggle_perspective, apply_camera_zoom)
+                    .run_if(in_state(GameState::Playing)),
+            )
             .add_systems(Last, move_camera.run_if(in_state(GameState::Playing)));
     }
 }
@@ -55,6 +64,43 @@ pub enum CameraAction {
     Right,
     RotateLeft,
     RotateRight,
+    ToggleRotationSnapping,
+    CycleMode,
+    TogglePerspective,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum CameraMode {
+    /// Follows the entity with a [CameraFocus].
+    #[default]
+    Follow,
+    /// Moved around independently, the player stands still.
+    Free,
+    /// Looks at the center of the world from far away.
+    Overview,
+}
+
+#[derive(Component)]
+pub struct CameraZoom {
+    pub target: f32,
+    current: f32,
+}
+
+const FOLLOW_SPEED: f32 = 8.0;
+const ZOOM_SPEED: f32 = 10.0;
+const OVERVIEW_ZOOM: f32 = MAX_ZOOM;
+
+#[derive(Resource, Default)]
+pub struct CameraRotationSettings {
+    /// Rotate in 90° steps instead of continuously, keeping the grid aligned with the screen.
+    pub snap_to_quarter_turns: bool,
+}
+
+const SNAPPED_ROTATION_SPEED: f32 = 6.0;
+
+/// The rotation of the camera around the y axis, in radians.
+pub fn camera_yaw(camera_parent: &Transform) -> f32 {
+    camera_parent.rotation.to_euler(EulerRot::YXZ).0
 }
 
 const CAMERA_OFFSET_TO_PLAYER: Vec3 = Vec3::new(0.0, 16.0, 20.0);
@@ -70,13 +116,13 @@ fn init(mut commands: Commands) {
                     rotation: Quat::from_rotation_x(-0.65),
                     ..default()
                 },
-                projection: Projection::Orthographic(OrthographicProjection {
-                    scale: 1.0,
-                    scaling_mode: ScalingMode::WindowSize(60.0),
-                    ..default()
-                }),
+                projection: orthographic_projection(),
                 ..default()
             },
+            CameraZoom {
+                target: 1.0,
+                current: 1.0,
+            },
             RaycastSource::<TileRaycastSet>::new_cursor(),
         ))
         .id();
@@ -96,62 +142,58 @@ fn init(mut commands: Commands) {
 
 fn move_camera(
     time: Res<Time>,
+    camera_mode: Res<State<CameraMode>>,
+    world_data: Res<WorldData>,
+    mut rotation_settings: ResMut<CameraRotationSettings>,
+    mut snapped_target_yaw: Local<Option<f32>>,
     camera_focus: Query<&Transform, (With<CameraFocus>, Without<MainCameraParent>)>,
     mut camera: Query<(&mut Transform, &ActionState<CameraAction>), With<MainCameraParent>>,
 ) {
     let (mut camera_transform, action_state) = camera.single_mut();
-    let delta = match camera_focus.get_single() {
-        Ok(camera_focus) => camera_focus.translation - camera_transform.translation,
-        Err(QuerySingleError::NoEntities(_)) => {
-            let mut dir;
-            if action_state.pressed(&CameraAction::Move) {
-                dir = action_state
-                    .clamped_axis_pair(&CameraAction::Move)
-                    .unwrap()
-                    .xy()
-                    .extend(0.0);
-            } else {
-                dir = Vec3::ZERO;
-            }
-
-            if action_state.pressed(&CameraAction::Up) {
-                dir.z -= 1.0;
-            }
-            if action_state.pressed(&CameraAction::Down) {
-                dir.z += 1.0;
-            }
-            if action_state.pressed(&CameraAction::Right) {
-                dir.x += 1.0;
+    let follow_factor = 1.0 - (-FOLLOW_SPEED * time.delta_seconds()).exp();
+    let delta = match camera_mode.get() {
+        CameraMode::Follow => match camera_focus.get_single() {
+            Ok(camera_focus) => {
+                (camera_focus.translation - camera_transform.translation) * follow_factor
             }
-            if action_state.pressed(&CameraAction::Left) {
-                dir.x -= 1.0;
-            }
-
-            let speed = {
-                if action_state.pressed(&CameraAction::Superspeed) {
-                    SPEED * SUPERSPEED_MULTIPLIER
-                } else {
-                    SPEED
-                }
-            };
-            if dir.length() > 1.0 {
-                if let Some(dir) = dir.try_normalize() {
-                    dir * speed * time.delta_seconds()
-                } else {
-                    Vec3::ZERO
-                }
-            } else {
-                dir * speed * time.delta_seconds()
+            Err(QuerySingleError::NoEntities(_)) => Vec3::ZERO,
+            Err(QuerySingleError::MultipleEntities(e)) => {
+                error!("Multiple Entities with CameraFocus component: {}", e);
+                Vec3::ZERO
             }
+        },
+        CameraMode::Free => {
+            let delta = free_camera_movement(&time, action_state);
+            Quat::from_rotation_y(camera_yaw(&camera_transform)) * delta
         }
-        Err(QuerySingleError::MultipleEntities(e)) => {
-            error!("Multiple Entities with CameraFocus component: {}", e);
-            Vec3::ZERO
+        CameraMode::Overview => {
+            (world_center(&world_data) - camera_transform.translation) * follow_factor
         }
     };
 
     camera_transform.translation += delta;
 
+    if action_state.just_pressed(&CameraAction::ToggleRotationSnapping) {
+        rotation_settings.snap_to_quarter_turns = !rotation_settings.snap_to_quarter_turns;
+    }
+
+    if rotation_settings.snap_to_quarter_turns {
+        let current_yaw = camera_yaw(&camera_transform);
+        let target_yaw =
+            snapped_target_yaw.get_or_insert((current_yaw / FRAC_PI_2).round() * FRAC_PI_2);
+        if action_state.just_pressed(&CameraAction::RotateLeft) {
+            *target_yaw -= FRAC_PI_2;
+        } else if action_state.just_pressed(&CameraAction::RotateRight) {
+            *target_yaw += FRAC_PI_2;
+        }
+
+        let remaining = (*target_yaw - current_yaw + PI).rem_euclid(TAU) - PI;
+        let step = (SNAPPED_ROTATION_SPEED * time.delta_seconds()).min(remaining.abs());
+        camera_transform.rotate_local_y(step * remaining.signum());
+        return;
+    }
+
+    *snapped_target_yaw = None;
     let rotation_dir: Option<f32> = if action_state.pressed(&CameraAction::RotateLeft) {
         Some(-1.0)
     } else if action_state.pressed(&CameraAction::RotateRight) {
@@ -165,25 +207,150 @@ fn move_camera(
     }
 }
 
+fn free_camera_movement(time: &Time, action_state: &ActionState<CameraAction>) -> Vec3 {
+    let mut dir;
+    if action_state.pressed(&CameraAction::Move) {
+        let stick = action_state
+            .clamped_axis_pair(&CameraAction::Move)
+            .unwrap()
+            .xy();
+        dir = Vec3::new(stick.x, 0.0, -stick.y);
+    } else {
+        dir = Vec3::ZERO;
+    }
+
+    if action_state.pressed(&CameraAction::Up) {
+        dir.z -= 1.0;
+    }
+    if action_state.pressed(&CameraAction::Down) {
+        dir.z += 1.0;
+    }
+    if action_state.pressed(&CameraAction::Right) {
+        dir.x += 1.0;
+    }
+    if action_state.pressed(&CameraAction::Left) {
+        dir.x -= 1.0;
+    }
+
+    let speed = {
+        if action_state.pressed(&CameraAction::Superspeed) {
+            SPEED * SUPERSPEED_MULTIPLIER
+        } else {
+            SPEED
+        }
+    };
+    if dir.length() > 1.0 {
+        if let Some(dir) = dir.try_normalize() {
+            dir * speed * time.delta_seconds()
+        } else {
+            Vec3::ZERO
+        }
+    } else {
+        dir * speed * time.delta_seconds()
+    }
+}
+
+/// The center of all generated chunks.
+fn world_center(world_data: &WorldData) -> Vec3 {
+    if world_data.chunks.is_empty() {
+        return Vec3::ZERO;
+    }
+
+    let sum = world_data
+        .chunks
+        .keys()
+        .fold(Vec2::ZERO, |sum, chunk| sum + chunk.as_vec2());
+    let center_chunk = sum / world_data.chunks.len() as f32;
+    let center = center_chunk * CHUNK_SIZE as f32 + Vec2::splat(CHUNK_SIZE as f32 * 0.5 - 0.5);
+
+    Vec3::new(center.x, 0.0, center.y)
+}
+
+fn cycle_camera_mode(
+    camera_mode: Res<State<CameraMode>>,
+    mut next_camera_mode: ResMut<NextState<CameraMode>>,
+    action_state: Query<&ActionState<CameraAction>>,
+) {
+    let Ok(action_state) = action_state.get_single() else {
+        return;
+    };
+
+    if action_state.just_pressed(&CameraAction::CycleMode) {
+        next_camera_mode.set(match camera_mode.get() {
+            CameraMode::Follow => CameraMode::Free,
+            CameraMode::Free => CameraMode::Overview,
+            CameraMode::Overview => CameraMode::Follow,
+        });
+    }
+}
+
+fn toggle_perspective(
+    mut query: Query<(&mut Projection, &Parent), With<MainCamera>>,
+    action_state: Query<&ActionState<CameraAction>>,
+) {
+    let (mut projection, parent) = query.single_mut();
+    let Ok(action_state) = action_state.get(parent.get()) else {
+        return;
+    };
+
+    if !action_state.just_pressed(&CameraAction::TogglePerspective) {
+        return;
+    }
+
+    *projection = match *projection {
+        Projection::Orthographic(_) => Projection::Perspective(PerspectiveProjection::default()),
+        Projection::Perspective(_) => orthographic_projection(),
+    };
+}
+
+fn orthographic_projection() -> Projection {
+    Projection::Orthographic(OrthographicProjection {
+        scale: 1.0,
+        scaling_mode: ScalingMode::WindowSize(60.0),
+        ..default()
+    })
+}
+
 const MAX_ZOOM: f32 = 4.0;
 const MIN_ZOOM: f32 = 0.5;
 
 fn zoom_camera(
-    mut query: Query<(&mut Projection, &Parent), With<MainCamera>>,
+    mut query: Query<(&mut CameraZoom, &Parent), With<MainCamera>>,
     action_state: Query<&ActionState<CameraAction>>,
 ) {
-    let (projection, parent) = query.single_mut();
+    let (mut zoom, parent) = query.single_mut();
     let action_state = action_state
         .get(parent.get())
         .expect("Main Camera should always have a parent with action states!");
 
-    let Projection::Orthographic(projection) = projection.into_inner() else {
-        error!("Zooming isn't yet supported for perspective cameras.");
-        return;
+    if let Some(direction) = zoom_direction(action_state, zoom.target) {
+        zoom.target += 0.20 * direction;
+    }
+}
+
+/// Smoothly moves the zoom towards its target. Orthographic cameras are scaled, perspective cameras move further away.
+fn apply_camera_zoom(
+    time: Res<Time>,
+    camera_mode: Res<State<CameraMode>>,
+    mut query: Query<(&mut CameraZoom, &mut Projection, &mut Transform), With<MainCamera>>,
+) {
+    let (mut zoom, projection, mut transform) = query.single_mut();
+    let target = match camera_mode.get() {
+        CameraMode::Overview => OVERVIEW_ZOOM,
+        CameraMode::Follow | CameraMode::Free => zoom.target,
     };
 
-    if let Some(direction) = zoom_direction(action_state, projection.scale) {
-        projection.scale += 0.20 * direction;
+    let factor = 1.0 - (-ZOOM_SPEED * time.delta_seconds()).exp();
+    zoom.current += (target - zoom.current) * factor;
+
+    match projection.into_inner() {
+        Projection::Orthographic(projection) => {
+            projection.scale = zoom.current;
+            transform.translation = CAMERA_OFFSET_TO_PLAYER;
+        }
+        Projection::Perspective(_) => {
+            transform.translation = CAMERA_OFFSET_TO_PLAYER * zoom.current;
+        }
     }
 }
 
@@ -235,6 +402,9 @@ fn default_input_map_camera() -> InputMap<CameraAction> {
 
     input_map.insert(CameraAction::RotateLeft, KeyCode::KeyQ);
     input_map.insert(CameraAction::RotateRight, KeyCode::KeyE);
+    input_map.insert(CameraAction::ToggleRotationSnapping, KeyCode::KeyT);
+    input_map.insert(CameraAction::CycleMode, KeyCode::KeyC);
+    input_map.insert(CameraAction::TogglePerspective, KeyCode::KeyP);
 
     input_map
 }
@@ -0,0 +1,348 @@
+use std::time::Duration;
+
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::{in_state, EventReader, EventWriter, IntoSystemConfigs, Query, Res, ResMut};
+use bevy::tasks::ComputeTaskPool;
+use bevy_sprite3d::AtlasSprite3dComponent;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use data::prelude::{AllItems, BuildCost, DoorDefinition};
+
+use crate::game::tilemap::helpers::neighbour_in_direction;
+use crate::prelude::chunk_data::{ChunkData, CropData};
+use crate::prelude::loaded_chunks::LoadedChunks;
+use crate::prelude::simulation_time::{DayStartedEvent, SimulationTick};
+use crate::prelude::update_tile_event::UpdateTileEvent;
+use crate::prelude::{
+    CardinalDirection, ChunkPos, GameState, MapPos, SimulationTime, TilePos, WorldData, CHUNK_SIZE,
+};
+
+/// How likely it is for weeds to grow on an empty tilled tile each day.
+const WEED_CHANCE_PER_DAY: f64 = 0.1;
+/// How likely it is for weeds to spread onto a random neighbouring tile each day.
+const WEED_SPREAD_CHANCE_PER_DAY: f64 = 0.05;
+
+pub struct ChunkSimulationPlugin;
+impl Plugin for ChunkSimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, simulate_world.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Everything that changed during a simulation step, so loaded chunks can update their visuals.
+#[derive(Default)]
+pub struct SimulationChanges {
+    pub tiles: Vec<MapPos>,
+    pub crops: Vec<MapPos>,
+}
+
+/// The outcome of simulating a single chunk. Effects reaching other tiles are applied later, during the merge.
+struct ChunkSimulationResult {
+    chunk_pos: ChunkPos,
+    changes: SimulationChanges,
+    spreading_weeds: Vec<MapPos>,
+}
+
+/// Loaded chunks are simulated every frame, all others once a day or right before they get loaded again.
+pub fn simulate_world(
+    mut world_data: ResMut<WorldData>,
+    loaded_chunks: Res<LoadedChunks>,
+    simulation_time: Res<SimulationTime>,
+    all_items: Res<AllItems>,
+    mut day_started_events: EventReader<DayStartedEvent>,
+    mut update_tile_events: EventWriter<UpdateTileEvent>,
+    mut sprites: Query<&mut AtlasSprite3dComponent>,
+) {
+    let is_new_day = day_started_events.read().count() > 0;
+    let changes = simulate_chunks(
+        &mut world_data,
+        |chunk_pos| is_new_day || loaded_chunks.chunks.contains_key(chunk_pos),
+        simulation_time.now(),
+        &all_items,
+    );
+
+    show_simulation_changes(
+        &changes,
+        &world_data,
+        &loaded_chunks,
+        &mut update_tile_events,
+        &mut sprites,
+    );
+}
+
+/// Catches up all matching chunks to now, each one on its own task.
+///
+/// This happens in two phases. First, every chunk is simulated on its own and only ever looks at
+/// and changes its own tiles, so the tasks can't observe each other. Then, everything which reaches
+/// into other tiles, like spreading weeds, is applied to the world in order of the chunk positions.
+/// Spreading therefore always sees the state after all chunks have been simulated, and the outcome
+/// doesn't depend on the order in which chunks are stored or tasks finish.
+pub fn simulate_chunks(
+    world_data: &mut WorldData,
+    should_simulate: impl Fn(&ChunkPos) -> bool,
+    now: SimulationTick,
+    all_items: &AllItems,
+) -> SimulationChanges {
+    let results = ComputeTaskPool::get().scope(|scope| {
+        for (chunk_pos, chunk) in world_data.chunks.iter_mut() {
+            if !should_simulate(chunk_pos) {
+                continue;
+            }
+
+            let chunk_pos = *chunk_pos;
+            scope.spawn(async move { simulate_chunk(chunk_pos, chunk, now, all_items) });
+        }
+    });
+
+    merge_chunk_results(world_data, results)
+}
+
+/// The second phase of [simulate_chunks], applying everything which reaches beyond a single chunk.
+fn merge_chunk_results(
+    world_data: &mut WorldData,
+    mut results: Vec<ChunkSimulationResult>,
+) -> SimulationChanges {
+    // Chunks are stored in a HashMap, so their order needs to be fixed before anything can be merged
+    results.sort_by_key(|result| (result.chunk_pos.x, result.chunk_pos.y));
+
+    let mut changes = SimulationChanges::default();
+    for result in results {
+        changes.tiles.extend(result.changes.tiles);
+        changes.crops.extend(result.changes.crops);
+
+        for pos in result.spreading_weeds {
+            let Some(chunk) = world_data.chunks.get_mut(&pos.chunk) else {
+                continue;
+            };
+            if chunk.crops.contains_key(&pos.tile) || chunk.props.contains_key(&pos.tile) {
+                continue;
+            }
+
+            let tile = chunk.at_pos_mut(&pos.tile);
+            if tile.is_tilled && !tile.has_weeds {
+                tile.has_weeds = true;
+                changes.tiles.push(pos);
+            }
+        }
+    }
+
+    changes
+}
+
+/// Computes everything that would have happened inside the chunk since it was last simulated, all at once.
+fn simulate_chunk(
+    chunk_pos: ChunkPos,
+    chunk: &mut ChunkData,
+    now: SimulationTick,
+    all_items: &AllItems,
+) -> ChunkSimulationResult {
+    let mut result = ChunkSimulationResult {
+        chunk_pos,
+        changes: SimulationChanges::default(),
+        spreading_weeds: Vec::new(),
+    };
+
+    for (tile_pos, crop) in chunk.crops.iter_mut() {
+        if grow_crop(crop, now, all_items) {
+            result.changes.crops.push(MapPos::new(chunk_pos, *tile_pos));
+        }
+    }
+
+    let days_passed = now.day().saturating_sub(chunk.last_simulated.day());
+    chunk.last_simulated = now;
+    if days_passed == 0 {
+        return result;
+    }
+
+    // Every chunk rolls its own dice, so the order in which tasks finish doesn't matter
+    let mut rng = StdRng::seed_from_u64(chunk_seed(chunk_pos, now.day()));
+    let chance_within_days = |chance_per_day: f64| {
+        1.0 - (1.0 - chance_per_day).powi(days_passed.min(i32::MAX as u64) as i32)
+    };
+    let weed_chance = chance_within_days(WEED_CHANCE_PER_DAY);
+    let spread_chance = chance_within_days(WEED_SPREAD_CHANCE_PER_DAY);
+
+    for y in 0..CHUNK_SIZE as u32 {
+        for x in 0..CHUNK_SIZE as u32 {
+            let tile_pos = TilePos::new(x, y);
+            let is_empty =
+                !chunk.crops.contains_key(&tile_pos) && !chunk.props.contains_key(&tile_pos);
+
+            let tile = chunk.at_mut(x, y);
+            // Water dries up by the next day
+            tile.is_watered = false;
+
+            if tile.has_weeds {
+                if rng.gen_bool(spread_chance) {
+                    let direction = CardinalDirection::ALL[rng.gen_range(0..4)];
+                    let (target_chunk, target_tile) =
+                        neighbour_in_direction(&chunk_pos, &tile_pos, direction);
+                    result
+                        .spreading_weeds
+                        .push(MapPos::new(target_chunk, target_tile));
+                }
+            } else if tile.is_tilled && is_empty && rng.gen_bool(weed_chance) {
+                tile.has_weeds = true;
+                result.changes.tiles.push(MapPos::new(chunk_pos, tile_pos));
+            }
+        }
+    }
+
+    result
+}
+
+fn chunk_seed(chunk_pos: ChunkPos, day: u64) -> u64 {
+    let position = ((chunk_pos.x as u32 as u64) << 32) | chunk_pos.y as u32 as u64;
+    position ^ day.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+/// Advances the crop by all stages it would have reached until now. Returns whether it grew at all.
+fn grow_crop(crop: &mut CropData, now: SimulationTick, all_items: &AllItems) -> bool {
+    let Some(next_stage_at) = crop.next_stage_at else {
+        return false;
+    };
+    if next_stage_at > now {
+        return false;
+    }
+    let Some(definition) = all_items.crops.get(&crop.crop_id) else {
+        return false;
+    };
+
+    // The next stage is reached at next_stage_at, every further one a full growth time later
+    let growth_millis = (definition.growth_time_per_stage.as_millis() as u64).max(1);
+    let stages_grown = 1 + now.duration_since(next_stage_at).as_millis() as u64 / growth_millis;
+    let final_stage = definition.stages.saturating_sub(1);
+
+    crop.stage = (crop.stage as u64 + stages_grown).min(final_stage as u64) as u8;
+    crop.next_stage_at = if crop.stage < final_stage {
+        Some(next_stage_at.after(Duration::from_millis(stages_grown * growth_millis)))
+    } else {
+        None
+    };
+
+    true
+}
+
+/// Refreshes the visuals of everything that changed inside loaded chunks.
+pub fn show_simulation_changes(
+    changes: &SimulationChanges,
+    world_data: &WorldData,
+    loaded_chunks: &LoadedChunks,
+    update_tile_events: &mut EventWriter<UpdateTileEvent>,
+    sprites: &mut Query<&mut AtlasSprite3dComponent>,
+) {
+    for pos in changes.tiles.iter() {
+        if loaded_chunks.chunks.contains_key(&pos.chunk) {
+            update_tile_events.send(UpdateTileEvent::new(pos.chunk, pos.tile));
+        }
+    }
+
+    for pos in changes.crops.iter() {
+        let Some(entity) = loaded_chunks
+            .chunks
+            .get(&pos.chunk)
+            .and_then(|loaded_data| loaded_data.crops.get(&pos.tile))
+        else {
+            continue;
+        };
+        let Some(crop) = world_data
+            .chunks
+            .get(&pos.chunk)
+            .and_then(|chunk| chunk.crops.get(&pos.tile))
+        else {
+            continue;
+        };
+
+        // TODO: Move that into an event, so we can also play sound effects and animations when necessary
+        if let Ok(mut sprite) = sprites.get_mut(*entity) {
+            sprite.index = crop.stage as usize;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::tasks::TaskPool;
+    use bevy::utils::HashMap;
+
+    use super::*;
+
+    /// A few chunks full of tilled soil, with weeds scattered around so some of them sit on chunk borders.
+    fn test_world() -> WorldData {
+        let mut world_data = WorldData {
+            chunks: HashMap::new(),
+        };
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let mut chunk = ChunkData::default();
+                for tile_y in 0..CHUNK_SIZE as u32 {
+                    for tile_x in 0..CHUNK_SIZE as u32 {
+                        let tile = chunk.at_mut(tile_x, tile_y);
+                        tile.is_tilled = true;
+                        tile.has_weeds = (tile_x * 7 + tile_y * 3) % 5 == 0;
+                    }
+                }
+                world_data.chunks.insert(ChunkPos::new(x, y), chunk);
+            }
+        }
+        world_data
+    }
+
+    fn empty_items() -> AllItems {
+        AllItems {
+            crops: HashMap::new(),
+            props: HashMap::new(),
+            edge_structures: HashMap::new(),
+            resources: HashMap::new(),
+            door: DoorDefinition {
+                build_cost: BuildCost::default(),
+            },
+        }
+    }
+
+    fn weeds(world_data: &WorldData) -> Vec<MapPos> {
+        let mut chunk_positions: Vec<ChunkPos> = world_data.chunks.keys().copied().collect();
+        chunk_positions.sort_by_key(|chunk_pos| (chunk_pos.x, chunk_pos.y));
+
+        let mut result = Vec::new();
+        for chunk_pos in chunk_positions {
+            let chunk = &world_data.chunks[&chunk_pos];
+            for y in 0..CHUNK_SIZE as u32 {
+                for x in 0..CHUNK_SIZE as u32 {
+                    if chunk.at(x, y).has_weeds {
+                        result.push(MapPos::new(chunk_pos, TilePos::new(x, y)));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn parallel_simulation_matches_sequential_simulation() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let all_items = empty_items();
+        let now = SimulationTick::from_duration(Duration::from_secs(60 * 60 * 24 * 20));
+
+        let mut parallel = test_world();
+        let parallel_changes = simulate_chunks(&mut parallel, |_| true, now, &all_items);
+
+        let mut sequential = test_world();
+        let mut chunk_positions: Vec<ChunkPos> = sequential.chunks.keys().copied().collect();
+        chunk_positions.sort_by_key(|chunk_pos| (chunk_pos.x, chunk_pos.y));
+        let results = chunk_positions
+            .into_iter()
+            .map(|chunk_pos| {
+                let chunk = sequential.chunks.get_mut(&chunk_pos).unwrap();
+                simulate_chunk(chunk_pos, chunk, now, &all_items)
+            })
+            .collect();
+        let sequential_changes = merge_chunk_results(&mut sequential, results);
+
+        assert_eq!(parallel_changes.tiles, sequential_changes.tiles);
+        assert_eq!(parallel_changes.crops, sequential_changes.crops);
+        assert_eq!(weeds(&parallel), weeds(&sequential));
+        assert_ne!(weeds(&parallel), weeds(&test_world()));
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::math::{IVec2, Vec3};
+use bevy::prelude::Component;
+
+use data::prelude::AllItems;
+
+use crate::prelude::{CardinalDirection, MapPos, WorldData};
+
+/// Makes an entity collide with walls, solid props and the edges of the world while moving.
+#[derive(Component)]
+pub struct Collider {
+    /// Half the width of the square used for collision checks.
+    pub radius: f32,
+}
+
+/// Moves the collider by delta, one axis at a time, so blocked movement slides along whatever is in the way.
+pub fn move_and_slide(
+    world_data: &WorldData,
+    all_items: &AllItems,
+    collider: &Collider,
+    position: Vec3,
+    delta: Vec3,
+) -> Vec3 {
+    let mut result = position;
+
+    let moved_x = result + Vec3::new(delta.x, 0.0, 0.0);
+    if !is_move_blocked(world_data, all_items, collider, result, moved_x) {
+        result = moved_x;
+    }
+
+    let moved_z = result + Vec3::new(0.0, 0.0, delta.z);
+    if !is_move_blocked(world_data, all_items, collider, result, moved_z) {
+        result = moved_z;
+    }
+
+    result
+}
+
+fn is_move_blocked(
+    world_data: &WorldData,
+    all_items: &AllItems,
+    collider: &Collider,
+    from: Vec3,
+    to: Vec3,
+) -> bool {
+    let (old_min, old_max) = overlapped_tiles(from, collider.radius);
+    let (new_min, new_max) = overlapped_tiles(to, collider.radius);
+
+    for z in new_min.y..=new_max.y {
+        for x in new_min.x..=new_max.x {
+            let tile = IVec2::new(x, z);
+            let already_overlapping = tile.cmpge(old_min).all() && tile.cmple(old_max).all();
+            if !already_overlapping && !is_tile_walkable(world_data, all_items, tile) {
+                return true;
+            }
+        }
+    }
+
+    // Walls sit on the edges between tiles, so we only need to look at the edges we are crossing.
+    let crosses_blocked_edge = |tiles: &mut dyn Iterator<Item = IVec2>,
+                                direction: CardinalDirection| {
+        tiles.any(|tile| is_edge_blocked(world_data, all_items, tile, direction))
+    };
+
+    (new_max.x > old_max.x
+        && crosses_blocked_edge(
+            &mut (new_min.y..=new_max.y).map(|z| IVec2::new(old_max.x, z)),
+            CardinalDirection::East,
+        ))
+        || (new_min.x < old_min.x
+            && crosses_blocked_edge(
+                &mut (new_min.y..=new_max.y).map(|z| IVec2::new(old_min.x, z)),
+                CardinalDirection::West,
+            ))
+        || (new_max.y > old_max.y
+            && crosses_blocked_edge(
+                &mut (new_min.x..=new_max.x).map(|x| IVec2::new(x, old_max.y)),
+                CardinalDirection::South,
+            ))
+        || (new_min.y < old_min.y
+            && crosses_blocked_edge(
+                &mut (new_min.x..=new_max.x).map(|x| IVec2::new(x, old_min.y)),
+                CardinalDirection::North,
+            ))
+}
+
+/// The global positions of the first and last tile touched by the square around position.
+fn overlapped_tiles(position: Vec3, radius: f32) -> (IVec2, IVec2) {
+    (
+        IVec2::new(
+            (position.x - radius).round() as i32,
+            (position.z - radius).round() as i32,
+        ),
+        IVec2::new(
+            (position.x + radius).round() as i32,
+            (position.z + radius).round() as i32,
+        ),
+    )
+}
+
+/// Whether something blocks the edge of the given tile, placed either by this tile or its neighbour.
+pub fn is_edge_blocked(
+    world_data: &WorldData,
+    all_items: &AllItems,
+    tile: IVec2,
+    direction: CardinalDirection,
+) -> bool {
+    let offset = direction.as_vec3();
+    let neighbour = tile + IVec2::new(offset.x as i32, offset.z as i32);
+
+    is_blocked_at(world_data, all_items, tile, direction)
+        || is_blocked_at(world_data, all_items, neighbour, direction.opposite())
+}
+
+fn is_blocked_at(
+    world_data: &WorldData,
+    all_items: &AllItems,
+    tile: IVec2,
+    direction: CardinalDirection,
+) -> bool {
+    let pos = MapPos::from_global_tile_pos(tile);
+    world_data.chunks.get(&pos.chunk).map_or(false, |chunk| {
+        chunk
+            .at_pos(&pos.tile)
+            .walls
+            .blocks_movement_at(direction, all_items)
+    })
+}
+
+/// Tiles outside of the generated world, on non-walkable ground or with solid props can't be entered.
+pub fn is_tile_walkable(world_data: &WorldData, all_items: &AllItems, tile: IVec2) -> bool {
+    let pos = MapPos::from_global_tile_pos(tile);
+    let Some(chunk) = world_data.chunks.get(&pos.chunk) else {
+        return false;
+    };
+
+    if !chunk.at_pos(&pos.tile).ground_type.is_walkable() {
+        return false;
+    }
+
+    match chunk.props.get(&pos.tile) {
+        Some(prop) => !all_items
+            .props
+            .get(&prop.prop_id)
+            .map_or(false, |definition| definition.is_solid),
+        None => true,
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::math::Vec3;
+use bevy::prelude::{
+    in_state, Camera, Commands, Event, EventReader, GlobalTransform, IntoSystemConfigs, Local,
+    Query, Res, Time, With,
+};
+use bevy_egui::egui::{Align2, Color32, FontId, LayerId, Pos2};
+use bevy_egui::EguiContexts;
+
+use data::prelude::{AllItems, BuildCost, ItemId};
+
+use crate::game::drops::{add_drop_to_world, DropSpawnParams};
+use crate::prelude::camera::MainCamera;
+use crate::prelude::chunk_data::{DropData, DropId};
+use crate::prelude::loaded_chunks::LoadedChunks;
+use crate::prelude::simulation_time::SimulationTick;
+use crate::prelude::{AddedDrop, ChunkPos, GameState, MapPos, WorldData};
+
+/// How much of the build cost is given back when something gets torn down.
+pub const DEMOLITION_REFUND: f32 = 0.5;
+
+const FAILURE_MESSAGE_SECONDS: f32 = 2.0;
+const FAILURE_MESSAGE_HEIGHT: f32 = 1.0;
+const FAILURE_MESSAGE_RISE_SPEED: f32 = 0.3;
+const FAILURE_MESSAGE_FONT_SIZE: f32 = 14.0;
+
+pub struct ConstructionPlugin;
+impl Plugin for ConstructionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BuildFailedEvent>().add_systems(
+            Update,
+            show_build_failures.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Sent whenever something couldn't be built because the builder couldn't pay for it.
+#[derive(Event, Debug)]
+pub struct BuildFailedEvent {
+    pub pos: MapPos,
+    pub missing: Vec<(ItemId, u32)>,
+}
+
+struct FailureMessage {
+    text: String,
+    position: Vec3,
+    remaining_seconds: f32,
+}
+
+/// Shows a short message floating above the tile where building failed.
+fn show_build_failures(
+    mut contexts: EguiContexts,
+    mut events: EventReader<BuildFailedEvent>,
+    mut messages: Local<Vec<FailureMessage>>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    all_items: Res<AllItems>,
+    time: Res<Time>,
+) {
+    // Dragging out a line of walls fails once per wall, but one message per frame is plenty
+    if let Some(event) = events.read().last() {
+        let missing = event
+            .missing
+            .iter()
+            .map(|(item_id, amount)| format!("{} {}", amount, item_id.item_name(&all_items)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        messages.push(FailureMessage {
+            text: format!("Missing {}", missing),
+            position: event.pos.world_pos(FAILURE_MESSAGE_HEIGHT),
+            remaining_seconds: FAILURE_MESSAGE_SECONDS,
+        });
+    }
+
+    for message in messages.iter_mut() {
+        message.remaining_seconds -= time.delta_seconds();
+        message.position.y += FAILURE_MESSAGE_RISE_SPEED * time.delta_seconds();
+    }
+    messages.retain(|message| message.remaining_seconds > 0.0);
+
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
+
+    let painter = contexts.ctx_mut().layer_painter(LayerId::background());
+    for message in messages.iter() {
+        if let Some(screen_pos) = camera.world_to_viewport(camera_transform, message.position) {
+            painter.text(
+                Pos2::new(screen_pos.x, screen_pos.y),
+                Align2::CENTER_CENTER,
+                &message.text,
+                FontId::proportional(FAILURE_MESSAGE_FONT_SIZE),
+                Color32::LIGHT_RED,
+            );
+        }
+    }
+}
+
+/// Drops the refunded part of the build cost onto the given tile and returns where it ended up.
+pub fn drop_demolition_refund(
+    commands: &mut Commands,
+    drop_params: &mut DropSpawnParams,
+    world_data: &mut WorldData,
+    loaded_chunks: &mut LoadedChunks,
+    cost: &BuildCost,
+    pos: MapPos,
+) -> Vec<(ChunkPos, DropId, DropData)> {
+    let mut dropped = Vec::new();
+    for (item_id, amount) in cost.refund(DEMOLITION_REFUND) {
+        let drop = DropData {
+            item_id,
+            amount,
+            position: pos.world_pos(0.0),
+            pickup_blocked_until: SimulationTick::default(),
+        };
+
+        if let Some(AddedDrop::New(chunk, id) | AddedDrop::Merged(chunk, id)) = add_drop_to_world(
+            commands,
+            drop_params,
+            world_data,
+            loaded_chunks,
+            drop.clone(),
+        ) {
+            dropped.push((chunk, id, drop));
+        }
+    }
+
+    dropped
+}
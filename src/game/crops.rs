@@ -0,0 +1,35 @@
+use bevy::core::Name;
+use bevy::math::Vec3;
+use bevy::prelude::{default, BuildChildren, Commands, Entity, Transform};
+use bevy_sprite3d::{AtlasSprite3d, Sprite3dParams};
+
+use data::prelude::CropDefinition;
+
+use crate::prelude::{MapPos, SPRITE_DEFAULT_PIVOT, SPRITE_PIXELS_PER_METER};
+
+pub fn spawn_crop_entity(
+    commands: &mut Commands,
+    sprite_params: &mut Sprite3dParams,
+    chunk_parent: Entity,
+    pos: &MapPos,
+    definition: &CropDefinition,
+    stage: u8,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new("Plant"),
+            AtlasSprite3d {
+                atlas: definition.texture_atlas.clone(),
+                index: stage as usize,
+                transform: Transform::from_translation(
+                    pos.pos_inside_chunk(0.0) - Vec3::new(0.0, 0.0, -0.1),
+                ),
+                pixels_per_metre: SPRITE_PIXELS_PER_METER,
+                pivot: SPRITE_DEFAULT_PIVOT,
+                ..default()
+            }
+            .bundle(sprite_params),
+        ))
+        .set_parent(chunk_parent)
+        .id()
+}
@@ -1,40 +1,83 @@
+use std::time::Duration;
+
+use bevy::core::Name;
+use bevy::ecs::system::SystemParam;
 use bevy::log::error;
+use bevy::math::Vec3;
 use bevy::prelude::{
-    in_state, on_event, App, Commands, Component, Entity, Event, EventReader, EventWriter,
-    IntoSystemConfigs, Plugin, Query, Res, Time, Transform, Update, Without,
+    default, in_state, on_event, App, Camera, Changed, Commands, Component, Entity, Event,
+    EventReader, EventWriter, GlobalTransform, IntoSystemConfigs, PbrBundle, Plugin, Query,
+    RemovedComponents, Res, ResMut, Resource, Time, Transform, Update, With, Without,
 };
+use bevy::utils::HashMap;
+use bevy_egui::egui::{Align2, Color32, FontId, LayerId, Pos2};
+use bevy_egui::EguiContexts;
 use bevy_kira_audio::{Audio, AudioControl};
+use bevy_sprite3d::{AtlasSprite3d, Sprite3d, Sprite3dParams};
+use leafwing_input_manager::action_state::ActionState;
 use rand::Rng;
 
-use data::prelude::{CropId, ItemId};
+use data::prelude::{AllItems, ItemId};
 
-use crate::prelude::{DebugSounds, Inventory};
+use crate::prelude::camera::MainCamera;
+use crate::prelude::chunk_data::{DropData, DropId};
+use crate::prelude::loaded_chunks::LoadedChunks;
+use crate::prelude::spatial_index::SpatialIndex;
+use crate::prelude::{
+    ActiveTool, AddedDrop, ChunkPos, DebugMaterials, DebugMeshes, DebugSounds, FacingDirection,
+    Inventory, MapPos, PlayerAction, SimulationTime, WorldData, SPRITE_DEFAULT_PIVOT,
+    SPRITE_PIXELS_PER_METER,
+};
 use crate::{GameState, SoundEffectsSetting};
 
 const PICKUP_DISTANCE: f32 = 0.1;
 const DEFAULT_MAGNET_DISTANCE: f32 = 1.5;
 const DEFAULT_MAGNET_SPEED: f32 = 2.5;
+const COUNT_BADGE_HEIGHT: f32 = 0.8;
+const COUNT_BADGE_FONT_SIZE: f32 = 14.0;
+const DROPPED_PROP_SCALE: f32 = 0.5;
+const DROP_DISTANCE_IN_FRONT: f32 = 0.8;
+const PICKUP_COOLDOWN: Duration = Duration::from_secs(2);
 
 pub struct ItemPickupPlugin;
 impl Plugin for ItemPickupPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PickupItemDropEvent>().add_systems(
-            Update,
-            (
-                item_magnet_and_pickups.run_if(in_state(GameState::Playing)),
-                add_item_pickups_to_inventory.run_if(on_event::<PickupItemDropEvent>()),
-                play_pickup_sound
-                    .run_if(in_state(SoundEffectsSetting::On))
-                    .run_if(on_event::<PickupItemDropEvent>()),
-            ),
-        );
+        app.add_event::<PickupItemDropEvent>()
+            .init_resource::<ItemSpatialIndex>()
+            .add_systems(
+                Update,
+                (
+                    (update_drop_index, update_magnet_index).run_if(in_state(GameState::Playing)),
+                    item_magnet_and_pickups
+                        .after(update_drop_index)
+                        .after(update_magnet_index)
+                        .run_if(in_state(GameState::Playing)),
+                    pickup_drops_in_unloaded_chunks
+                        .after(update_magnet_index)
+                        .run_if(in_state(GameState::Playing)),
+                    draw_drop_count_badges.run_if(in_state(GameState::Playing)),
+                    drop_selected_item.run_if(in_state(GameState::Playing)),
+                    add_item_pickups_to_inventory.run_if(on_event::<PickupItemDropEvent>()),
+                    play_pickup_sound
+                        .run_if(in_state(SoundEffectsSetting::On))
+                        .run_if(on_event::<PickupItemDropEvent>()),
+                ),
+            );
     }
 }
 
+/// Spatial lookup for drop and magnet entities, so pickups stay cheap with lots of them around.
+#[derive(Resource, Default)]
+pub struct ItemSpatialIndex {
+    pub drops: SpatialIndex,
+    pub magnets: SpatialIndex,
+}
+
+/// Links a drop entity to its [DropData] inside [WorldData].
 #[derive(Component, Clone)]
 pub struct ItemDrop {
-    pub item_id: ItemId,
-    pub amount: u32,
+    pub chunk: ChunkPos,
+    pub id: DropId,
 }
 
 #[derive(Component)]
@@ -54,7 +97,8 @@ impl Default for ItemMagnet {
 
 #[derive(Event)]
 pub struct PickupItemDropEvent {
-    drop: ItemDrop,
+    item_id: ItemId,
+    amount: u32,
     entity: Entity,
 }
 
@@ -64,7 +108,7 @@ fn add_item_pickups_to_inventory(
 ) {
     for event in events.read() {
         if let Ok(mut inventory) = entities_with_inventory.get_mut(event.entity) {
-            inventory.add_item(&event.drop.item_id, event.drop.amount);
+            inventory.add_item(&event.item_id, event.amount);
         } else {
             error!(
                 "Pickup Item event for entity without an inventory: {:?}",
@@ -88,51 +132,449 @@ fn play_pickup_sound(
     }
 }
 
+fn update_drop_index(
+    mut index: ResMut<ItemSpatialIndex>,
+    moved_drops: Query<(Entity, &Transform), (With<ItemDrop>, Changed<Transform>)>,
+    mut removed_drops: RemovedComponents<ItemDrop>,
+) {
+    for entity in removed_drops.read() {
+        index.drops.remove(entity);
+    }
+
+    for (entity, transform) in moved_drops.iter() {
+        index.drops.update(entity, transform.translation);
+    }
+}
+
+fn update_magnet_index(
+    mut index: ResMut<ItemSpatialIndex>,
+    moved_magnets: Query<(Entity, &Transform), (With<ItemMagnet>, Changed<Transform>)>,
+    mut removed_magnets: RemovedComponents<ItemMagnet>,
+) {
+    for entity in removed_magnets.read() {
+        index.magnets.remove(entity);
+    }
+
+    for (entity, transform) in moved_magnets.iter() {
+        index.magnets.update(entity, transform.translation);
+    }
+}
+
 fn item_magnet_and_pickups(
     mut commands: Commands,
-    mut drops: Query<(Entity, &ItemDrop, &mut Transform), Without<ItemMagnet>>,
+    mut drops: Query<(&mut ItemDrop, &mut Transform), Without<ItemMagnet>>,
     mut pickup_events: EventWriter<PickupItemDropEvent>,
+    mut world_data: ResMut<WorldData>,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+    index: Res<ItemSpatialIndex>,
     targets: Query<(Entity, &Transform, &ItemMagnet)>,
     time: Res<Time>,
+    simulation_time: Res<SimulationTime>,
 ) {
-    for (entity, drop, mut drop_transform) in drops.iter_mut() {
-        let target = targets.iter().min_by(|a, b| {
-            a.1.translation
-                .distance(drop_transform.translation)
-                .total_cmp(&b.1.translation.distance(drop_transform.translation))
-        });
-
-        if let Some((target_entity, target_transform, magnet)) = target {
-            let delta = target_transform.translation - drop_transform.translation;
-            let distance = delta.length();
-            if distance < PICKUP_DISTANCE {
+    let now = simulation_time.now();
+
+    // Every drop is only attracted by the closest magnet in range
+    let mut closest_magnets: HashMap<Entity, (Entity, f32)> = HashMap::new();
+    for (magnet_entity, magnet_transform, magnet) in targets.iter() {
+        for drop_entity in index
+            .drops
+            .query(magnet_transform.translation, magnet.distance)
+        {
+            let Ok((drop, drop_transform)) = drops.get(drop_entity) else {
+                continue;
+            };
+            let can_be_picked_up = world_data
+                .chunks
+                .get(&drop.chunk)
+                .and_then(|chunk| chunk.drops.get(&drop.id))
+                .map_or(false, |data| data.can_be_picked_up(now));
+            if !can_be_picked_up {
+                continue;
+            }
+
+            let distance = magnet_transform
+                .translation
+                .distance(drop_transform.translation);
+            if distance >= magnet.distance {
+                continue;
+            }
+
+            let closest = closest_magnets
+                .entry(drop_entity)
+                .or_insert((magnet_entity, distance));
+            if distance < closest.1 {
+                *closest = (magnet_entity, distance);
+            }
+        }
+    }
+
+    for (entity, (target_entity, _)) in closest_magnets {
+        let Ok((mut drop, mut drop_transform)) = drops.get_mut(entity) else {
+            continue;
+        };
+        let Ok((_, target_transform, magnet)) = targets.get(target_entity) else {
+            continue;
+        };
+
+        let delta = target_transform.translation - drop_transform.translation;
+        let distance = delta.length();
+        if distance < PICKUP_DISTANCE {
+            if let Some(data) = remove_drop(&mut world_data, &mut loaded_chunks, &drop) {
                 pickup_events.send(PickupItemDropEvent {
-                    drop: drop.clone(),
+                    item_id: data.item_id,
+                    amount: data.amount,
                     entity: target_entity,
                 });
-                commands.entity(entity).despawn();
-            } else if distance < magnet.distance {
-                let dir = delta.normalize();
-                let speed = {
-                    let percentage = (magnet.distance - distance) / magnet.distance;
-                    let speed = magnet.speed * percentage.powf(1.5);
-                    if speed < magnet.speed * 0.1 {
-                        magnet.speed * 0.1
-                    } else {
-                        speed
-                    }
+            }
+            commands.entity(entity).despawn();
+        } else {
+            let dir = delta.normalize();
+            let speed = {
+                let percentage = (magnet.distance - distance) / magnet.distance;
+                let speed = magnet.speed * percentage.powf(1.5);
+                if speed < magnet.speed * 0.1 {
+                    magnet.speed * 0.1
+                } else {
+                    speed
+                }
+            };
+            drop_transform.translation += time.delta_seconds() * speed * dir;
+
+            move_drop(
+                &mut commands,
+                &mut world_data,
+                &mut loaded_chunks,
+                entity,
+                &mut drop,
+                drop_transform.translation,
+            );
+        }
+    }
+}
+
+/// Drops inside chunks which aren't loaded have no entity, so magnets reaching into those chunks collect them directly.
+fn pickup_drops_in_unloaded_chunks(
+    mut pickup_events: EventWriter<PickupItemDropEvent>,
+    mut world_data: ResMut<WorldData>,
+    loaded_chunks: Res<LoadedChunks>,
+    index: Res<ItemSpatialIndex>,
+    magnets: Query<(&Transform, &ItemMagnet)>,
+    simulation_time: Res<SimulationTime>,
+) {
+    let now = simulation_time.now();
+    for entity in index
+        .magnets
+        .buckets()
+        .flat_map(|(_, entities)| entities.iter())
+    {
+        let Ok((transform, magnet)) = magnets.get(*entity) else {
+            continue;
+        };
+
+        // The magnet might reach into neighbouring chunks while standing close to the border
+        let reach = Vec3::new(magnet.distance, 0.0, magnet.distance);
+        let min = MapPos::from_world_pos(transform.translation - reach).chunk;
+        let max = MapPos::from_world_pos(transform.translation + reach).chunk;
+
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let chunk_pos = ChunkPos::new(x, y);
+                if loaded_chunks.chunks.contains_key(&chunk_pos) {
+                    continue;
+                }
+                let Some(chunk) = world_data.chunks.get_mut(&chunk_pos) else {
+                    continue;
                 };
-                drop_transform.translation += time.delta_seconds() * speed * dir;
+
+                let picked_up: Vec<DropId> = chunk
+                    .drops
+                    .iter()
+                    .filter(|(_, drop)| {
+                        drop.can_be_picked_up(now)
+                            && drop.position.distance(transform.translation) < magnet.distance
+                    })
+                    .map(|(id, _)| *id)
+                    .collect();
+
+                for id in picked_up {
+                    let drop = chunk.drops.remove(&id).unwrap();
+                    pickup_events.send(PickupItemDropEvent {
+                        item_id: drop.item_id,
+                        amount: drop.amount,
+                        entity: *entity,
+                    });
+                }
             }
         }
     }
 }
 
-impl ItemDrop {
-    pub fn from_crop(crop_id: CropId, amount: u32) -> Self {
-        Self {
-            item_id: ItemId::Crop { crop_id },
+fn drop_selected_item(
+    mut commands: Commands,
+    mut drop_params: DropSpawnParams,
+    mut world_data: ResMut<WorldData>,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+    mut players: Query<(
+        &Transform,
+        &FacingDirection,
+        &ActionState<PlayerAction>,
+        &mut Inventory,
+    )>,
+    active_tool: Res<ActiveTool>,
+    simulation_time: Res<SimulationTime>,
+) {
+    for (transform, facing, action_state, mut inventory) in players.iter_mut() {
+        if !action_state.just_pressed(&PlayerAction::DropItem) {
+            continue;
+        }
+
+        let ActiveTool::Item(item_id) = *active_tool else {
+            continue;
+        };
+
+        let amount = inventory.take_all(&item_id);
+        if amount == 0 {
+            continue;
+        }
+
+        // The cooldown is stored with the drop, so it still applies after the chunk got unloaded
+        let drop = DropData {
+            item_id,
             amount,
+            position: transform.translation + facing.direction.as_vec3() * DROP_DISTANCE_IN_FRONT,
+            pickup_blocked_until: simulation_time.now().after(PICKUP_COOLDOWN),
+        };
+
+        let Some((chunk_pos, id)) = world_data.insert_drop(drop.clone()) else {
+            // Nowhere to put it, so we'll just keep it.
+            inventory.add_item(&item_id, amount);
+            continue;
+        };
+
+        if let Some(loaded_data) = loaded_chunks.chunks.get_mut(&chunk_pos) {
+            if let Some(entity) =
+                spawn_item_drop_entity(&mut commands, &mut drop_params, chunk_pos, id, &drop)
+            {
+                loaded_data.drops.insert(id, entity);
+            }
+        }
+    }
+}
+
+/// Updates the position of the drop inside [WorldData], moving it over to another chunk once it crosses the border.
+fn move_drop(
+    commands: &mut Commands,
+    world_data: &mut WorldData,
+    loaded_chunks: &mut LoadedChunks,
+    entity: Entity,
+    drop: &mut ItemDrop,
+    position: Vec3,
+) {
+    let Some(data) = world_data
+        .chunks
+        .get_mut(&drop.chunk)
+        .and_then(|chunk| chunk.drops.get_mut(&drop.id))
+    else {
+        return;
+    };
+    data.position = position;
+
+    let chunk_pos = MapPos::from_world_pos(position).chunk;
+    if chunk_pos == drop.chunk || !world_data.chunks.contains_key(&chunk_pos) {
+        return;
+    }
+
+    let Some(data) = remove_drop(world_data, loaded_chunks, drop) else {
+        return;
+    };
+    let Some((chunk_pos, id)) = world_data.insert_drop(data) else {
+        return;
+    };
+
+    drop.chunk = chunk_pos;
+    drop.id = id;
+    if let Some(loaded_data) = loaded_chunks.chunks.get_mut(&chunk_pos) {
+        loaded_data.drops.insert(id, entity);
+    } else {
+        // Drops inside unloaded chunks only exist as data
+        commands.entity(entity).despawn();
+    }
+}
+
+fn remove_drop(
+    world_data: &mut WorldData,
+    loaded_chunks: &mut LoadedChunks,
+    drop: &ItemDrop,
+) -> Option<DropData> {
+    if let Some(loaded_chunk) = loaded_chunks.chunks.get_mut(&drop.chunk) {
+        loaded_chunk.drops.remove(&drop.id);
+    }
+
+    world_data
+        .chunks
+        .get_mut(&drop.chunk)
+        .and_then(|chunk| chunk.drops.remove(&drop.id))
+}
+
+fn draw_drop_count_badges(
+    mut contexts: EguiContexts,
+    drops: Query<(&ItemDrop, &GlobalTransform)>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    world_data: Res<WorldData>,
+) {
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
+
+    let painter = contexts.ctx_mut().layer_painter(LayerId::background());
+    for (drop, transform) in drops.iter() {
+        let Some(data) = world_data
+            .chunks
+            .get(&drop.chunk)
+            .and_then(|chunk| chunk.drops.get(&drop.id))
+        else {
+            continue;
+        };
+
+        if data.amount < 2 {
+            continue;
+        }
+
+        let badge_position = transform.translation() + Vec3::Y * COUNT_BADGE_HEIGHT;
+        if let Some(screen_pos) = camera.world_to_viewport(camera_transform, badge_position) {
+            painter.text(
+                Pos2::new(screen_pos.x, screen_pos.y),
+                Align2::CENTER_CENTER,
+                data.amount.to_string(),
+                FontId::proportional(COUNT_BADGE_FONT_SIZE),
+                Color32::WHITE,
+            );
         }
     }
 }
+
+/// Everything needed to spawn the visual representation of a drop.
+#[derive(SystemParam)]
+pub struct DropSpawnParams<'w, 's> {
+    pub sprite_params: Sprite3dParams<'w, 's>,
+    pub all_items: Res<'w, AllItems>,
+    pub meshes: Res<'w, DebugMeshes>,
+    pub materials: Res<'w, DebugMaterials>,
+}
+
+/// Stores the drop inside [WorldData], merging it into nearby stacks, and spawns an entity for it if necessary.
+pub fn add_drop_to_world(
+    commands: &mut Commands,
+    drop_params: &mut DropSpawnParams,
+    world_data: &mut WorldData,
+    loaded_chunks: &mut LoadedChunks,
+    drop: DropData,
+) -> Option<AddedDrop> {
+    let Some(added_drop) = world_data.add_drop(drop.clone()) else {
+        error!("Unable to store drop at {}", drop.position);
+        return None;
+    };
+
+    if let AddedDrop::New(chunk_pos, id) = added_drop {
+        if let Some(loaded_data) = loaded_chunks.chunks.get_mut(&chunk_pos) {
+            if let Some(entity) =
+                spawn_item_drop_entity(commands, drop_params, chunk_pos, id, &drop)
+            {
+                loaded_data.drops.insert(id, entity);
+            }
+        }
+    }
+
+    Some(added_drop)
+}
+
+/// Spawns the visual representation of a drop stored inside [WorldData].
+pub fn spawn_item_drop_entity(
+    commands: &mut Commands,
+    drop_params: &mut DropSpawnParams,
+    chunk: ChunkPos,
+    id: DropId,
+    drop: &DropData,
+) -> Option<Entity> {
+    let all_items = &drop_params.all_items;
+    let mut entity_commands = match drop.item_id {
+        ItemId::Crop { crop_id } => {
+            let Some(crop) = all_items.crops.get(&crop_id) else {
+                error!("Unable to find crop with id {}", crop_id.0);
+                return None;
+            };
+
+            commands.spawn(
+                Sprite3d {
+                    transform: Transform::from_translation(drop.position),
+                    image: crop.harvested_sprite.clone(),
+                    pixels_per_metre: SPRITE_PIXELS_PER_METER,
+                    pivot: SPRITE_DEFAULT_PIVOT,
+                    ..default()
+                }
+                .bundle(&mut drop_params.sprite_params),
+            )
+        }
+        ItemId::Seed { crop_id } => {
+            let Some(crop) = all_items.crops.get(&crop_id) else {
+                error!("Unable to find crop with id {}", crop_id.0);
+                return None;
+            };
+
+            // Seeds look like the first growth stage of their crop, for now.
+            commands.spawn(
+                AtlasSprite3d {
+                    atlas: crop.texture_atlas.clone(),
+                    index: 0,
+                    transform: Transform::from_translation(drop.position),
+                    pixels_per_metre: SPRITE_PIXELS_PER_METER,
+                    pivot: SPRITE_DEFAULT_PIVOT,
+                    ..default()
+                }
+                .bundle(&mut drop_params.sprite_params),
+            )
+        }
+        ItemId::Prop { prop_id } => {
+            let Some(prop) = all_items.props.get(&prop_id) else {
+                error!("Unable to find prop with id {}", prop_id.0);
+                return None;
+            };
+
+            commands.spawn(PbrBundle {
+                mesh: prop.mesh.clone(),
+                material: prop.material.clone(),
+                transform: Transform {
+                    translation: drop.position + Vec3::Y * prop.height * DROPPED_PROP_SCALE * 0.5,
+                    scale: Vec3::splat(DROPPED_PROP_SCALE),
+                    ..default()
+                },
+                ..default()
+            })
+        }
+        ItemId::Resource { resource_id } => {
+            let Some(resource) = all_items.resources.get(&resource_id) else {
+                error!("Unable to find resource with id {}", resource_id.0);
+                return None;
+            };
+
+            commands.spawn(PbrBundle {
+                mesh: resource.mesh.clone(),
+                material: resource.material.clone(),
+                transform: Transform::from_translation(drop.position + Vec3::Y * 0.125),
+                ..default()
+            })
+        }
+        ItemId::Tool { .. } => commands.spawn(PbrBundle {
+            mesh: drop_params.meshes.tool.clone(),
+            material: drop_params.materials.wall.clone(),
+            transform: Transform::from_translation(drop.position + Vec3::Y * 0.05),
+            ..default()
+        }),
+    };
+
+    let entity = entity_commands
+        .insert((Name::new("Drop"), ItemDrop { chunk, id }))
+        .id();
+
+    Some(entity)
+}
@@ -0,0 +1,177 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::log::{error, info};
+use bevy::prelude::{
+    in_state, on_event, Component, EventReader, IntoSystemConfigs, Query, Res, ResMut, With,
+};
+use bevy_egui::egui::{Align2, Pos2};
+use bevy_egui::{egui, EguiContexts};
+
+use data::prelude::{AllItems, ItemId, ToolId, ToolTier};
+
+use crate::game::market::Market;
+use crate::prelude::simulation_time::DayStartedEvent;
+use crate::prelude::{ControlledByPlayer, GameState, Inventory, SimulationDate, WorldData};
+
+pub const STARTING_GOLD: u32 = 50;
+
+pub struct EconomyPlugin;
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                pay_out_shipping_bins.run_if(on_event::<DayStartedEvent>()),
+                shop_ui,
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Wallet {
+    pub gold: u32,
+}
+
+/// The base price of a single item of this kind, before market fluctuations. None if it can't be sold.
+pub fn sell_price(item_id: &ItemId, all_items: &AllItems) -> Option<u32> {
+    match item_id {
+        ItemId::Crop { crop_id } => all_items.crops.get(crop_id).map(|crop| crop.sell_price),
+        ItemId::Seed { crop_id } => all_items.crops.get(crop_id).map(|crop| crop.seed_price / 2),
+        ItemId::Resource { resource_id } => all_items
+            .resources
+            .get(resource_id)
+            .map(|resource| resource.price / 2),
+        ItemId::Tool { .. } | ItemId::Prop { .. } => None,
+    }
+}
+
+pub(crate) fn pay_out_shipping_bins(
+    mut day_started_events: EventReader<DayStartedEvent>,
+    mut world_data: ResMut<WorldData>,
+    mut wallets: Query<&mut Wallet, With<ControlledByPlayer>>,
+    mut market: ResMut<Market>,
+    simulation_date: Res<SimulationDate>,
+    all_items: Res<AllItems>,
+) {
+    day_started_events.clear();
+
+    let Ok(mut wallet) = wallets.get_single_mut() else {
+        error!("Unable to find a player wallet to pay shipping bin contents into!");
+        return;
+    };
+
+    let mut earnings = 0;
+    for chunk in world_data.chunks.values_mut() {
+        for prop in chunk.props.values_mut() {
+            let Some(inventory) = prop.inventory.as_mut() else {
+                continue;
+            };
+
+            for (item_id, amount) in &*inventory {
+                let price = market
+                    .current_price(item_id)
+                    .or_else(|| sell_price(item_id, &all_items))
+                    .unwrap_or(0);
+
+                earnings += price * amount;
+                market.record_sale(item_id, *amount, simulation_date.season());
+            }
+            inventory.clear();
+        }
+    }
+
+    if earnings > 0 {
+        info!("Shipping bins paid out {} gold.", earnings);
+        wallet.gold += earnings;
+    }
+}
+
+fn shop_ui(
+    mut contexts: EguiContexts,
+    mut customers: Query<(&mut Wallet, &mut Inventory), With<ControlledByPlayer>>,
+    all_items: Res<AllItems>,
+) {
+    let Ok((mut wallet, mut inventory)) = customers.get_single_mut() else {
+        return;
+    };
+
+    let mut crops: Vec<_> = all_items.crops.values().collect();
+    crops.sort_by_key(|crop| crop.id.0);
+    let mut resources: Vec<_> = all_items.resources.values().collect();
+    resources.sort_by_key(|resource| resource.id.0);
+
+    egui::Window::new("Shop")
+        .collapsible(true)
+        .default_open(false)
+        .resizable(false)
+        .anchor(Align2::RIGHT_BOTTOM, egui::Vec2::new(0.0, 0.0))
+        .fixed_pos(Pos2::new(5.0, 5.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(format!("Gold: {}", wallet.gold));
+            for crop in crops {
+                let item_id = ItemId::Seed { crop_id: crop.id };
+                let button = egui::Button::new(format!(
+                    "{}: {} Gold",
+                    item_id.item_name(&all_items),
+                    crop.seed_price
+                ));
+
+                if ui
+                    .add_enabled(wallet.gold >= crop.seed_price, button)
+                    .clicked()
+                {
+                    wallet.gold -= crop.seed_price;
+                    inventory.add_item(&item_id, 1);
+                }
+            }
+
+            ui.separator();
+            for resource in resources {
+                let item_id = ItemId::Resource {
+                    resource_id: resource.id,
+                };
+                let button = egui::Button::new(format!(
+                    "{}: {} Gold",
+                    item_id.item_name(&all_items),
+                    resource.price
+                ));
+
+                if ui
+                    .add_enabled(wallet.gold >= resource.price, button)
+                    .clicked()
+                {
+                    wallet.gold -= resource.price;
+                    inventory.add_item(&item_id, 1);
+                }
+            }
+
+            ui.separator();
+            for tool_id in [ToolId::Hoe, ToolId::Pickaxe, ToolId::Scythe] {
+                let tier = inventory.best_tool_tier(tool_id);
+                let Some(next_tier) = tier.next() else {
+                    continue;
+                };
+
+                let price = next_tier.upgrade_price();
+                let button = egui::Button::new(format!(
+                    "Upgrade {} to {}: {} Gold",
+                    tool_id, next_tier, price
+                ));
+
+                if ui.add_enabled(wallet.gold >= price, button).clicked() {
+                    wallet.gold -= price;
+                    if tier != ToolTier::Basic {
+                        inventory.remove_item(&ItemId::Tool { tool_id, tier }, 1);
+                    }
+                    inventory.add_item(
+                        &ItemId::Tool {
+                            tool_id,
+                            tier: next_tier,
+                        },
+                        1,
+                    );
+                }
+            }
+        });
+}
@@ -1,21 +1,29 @@
 use bevy::prelude::*;
-use bevy_sprite3d::{AtlasSprite3d, Sprite3d, Sprite3dParams};
 use leafwing_input_manager::action_state::ActionState;
 
-use data::prelude::{AllItems, CropId, ItemId, ToolId};
+use data::prelude::{AllItems, BuildCost, CropId, EdgeStructureId, ItemId, PropId, ToolId};
 
-use crate::game::drops::ItemDrop;
+use crate::game::camera::{camera_yaw, MainCameraParent};
+use crate::game::construction::{drop_demolition_refund, BuildFailedEvent};
+use crate::game::crops::spawn_crop_entity;
+use crate::game::drops::{add_drop_to_world, DropSpawnParams};
 use crate::game::map_pos::MapPos;
 use crate::game::player::PlayerAction;
-use crate::game::walls::build_and_spawn_wall_entity;
-use crate::prelude::chunk_data::CropData;
+use crate::game::props::spawn_prop_entity;
+use crate::game::undo::{UndoHistory, WorldCommand};
+use crate::game::walls::{
+    build_and_spawn_door_entity, build_and_spawn_wall_entity, find_edge_structure, WallChangedEvent,
+};
+use crate::prelude::chunk_data::{CropData, DropData, DropId, PropData};
 use crate::prelude::loaded_chunks::LoadedChunks;
-use crate::prelude::tile_cursor::TileCursor;
+use crate::prelude::simulation_time::SimulationTick;
+use crate::prelude::tile_cursor::{AreaSelection, CursorInputState, MouseCursorOnTile, TileCursor};
+use crate::prelude::tile_data::EdgeStructure;
 use crate::prelude::update_tile_event::UpdateTileEvent;
 use crate::prelude::GameState;
 use crate::prelude::{
-    ActiveTool, CardinalDirection, DebugMaterials, DebugMeshes, MouseCursorOverUiState,
-    SimulationTime, TilePos, WorldData, SPRITE_DEFAULT_PIVOT, SPRITE_PIXELS_PER_METER,
+    ActiveTool, AddedDrop, CardinalDirection, ChunkPos, Inventory, MouseCursorOverUiState,
+    SimulationTime, TilePos, WorldData,
 };
 
 pub struct InteractionPlugin;
@@ -24,25 +32,34 @@ impl Plugin for InteractionPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(ActiveTool::default())
             .insert_resource(BuildingRotation {
+                relative_direction: CardinalDirection::North,
                 direction: CardinalDirection::North,
             })
             .add_event::<CropDestroyedEvent>()
-            .add_event::<CropHarvestedEvent>()
             .add_event::<TileInteractionEvent>()
             .add_systems(
                 Update,
-                (select_active_tool, rotate_building).run_if(in_state(GameState::Playing)),
+                (
+                    select_active_tool,
+                    refresh_active_tool_tier.after(select_active_tool),
+                    rotate_building,
+                )
+                    .run_if(in_state(GameState::Playing)),
             )
             .add_systems(
                 Update,
-                detect_tile_interactions
-                    .run_if(in_state(MouseCursorOverUiState::NotOverUI))
+                (detect_tile_interactions, plan_walls)
+                    .run_if(
+                        in_state(MouseCursorOverUiState::NotOverUI)
+                            .or_else(in_state(CursorInputState::Gamepad)),
+                    )
                     .run_if(in_state(GameState::Playing)),
             )
             .add_systems(
                 Update,
                 process_tile_interactions
                     .after(detect_tile_interactions)
+                    .after(plan_walls)
                     .run_if(in_state(GameState::Playing)),
             )
             .add_systems(
@@ -51,51 +68,121 @@ impl Plugin for InteractionPlugin {
                     .after(process_tile_interactions)
                     .run_if(in_state(GameState::Playing))
                     .run_if(on_event::<CropDestroyedEvent>()),
-            )
-            .add_systems(
-                Update,
-                process_harvested_crops
-                    .after(process_tile_interactions)
-                    .run_if(in_state(GameState::Playing))
-                    .run_if(on_event::<CropHarvestedEvent>()),
             );
     }
 }
 
+const HOTBAR_SLOTS: [PlayerAction; 10] = [
+    PlayerAction::Hotbar1,
+    PlayerAction::Hotbar2,
+    PlayerAction::Hotbar3,
+    PlayerAction::Hotbar4,
+    PlayerAction::Hotbar5,
+    PlayerAction::Hotbar6,
+    PlayerAction::Hotbar7,
+    PlayerAction::Hotbar8,
+    PlayerAction::Hotbar9,
+    PlayerAction::Hotbar0,
+];
+
 fn select_active_tool(
     mut active_tool: ResMut<ActiveTool>,
-    action_state: Query<&ActionState<PlayerAction>>,
+    action_state: Query<(&ActionState<PlayerAction>, &Inventory)>,
+    all_items: Res<AllItems>,
+    mut selected_slot: Local<Option<usize>>,
 ) {
     let action_state = action_state.get_single();
     if action_state.is_err() {
         error!("PlayerAction State was missing!");
         return;
     }
-    let action_state = action_state.unwrap();
-    if action_state.just_pressed(&PlayerAction::Hotbar1) {
+    let (action_state, inventory) = action_state.unwrap();
+
+    let slot = if let Some(slot) = HOTBAR_SLOTS
+        .iter()
+        .position(|action| action_state.just_pressed(action))
+    {
+        slot
+    } else if action_state.just_pressed(&PlayerAction::NextTool) {
+        selected_slot.map_or(0, |slot| (slot + 1) % HOTBAR_SLOTS.len())
+    } else if action_state.just_pressed(&PlayerAction::PreviousTool) {
+        selected_slot.map_or(HOTBAR_SLOTS.len() - 1, |slot| {
+            (slot + HOTBAR_SLOTS.len() - 1) % HOTBAR_SLOTS.len()
+        })
+    } else {
+        return;
+    };
+
+    // Selecting the wall slot again cycles through all kinds of edge structures
+    if *selected_slot == Some(slot) {
+        if let ActiveTool::Wall(structure_id) = *active_tool {
+            // IDs aren't necessarily contiguous, so cycle through the ones which actually exist
+            let mut ids: Vec<EdgeStructureId> = all_items.edge_structures.keys().copied().collect();
+            ids.sort_by_key(|id| id.0);
+            if let Some(next) = ids
+                .iter()
+                .find(|id| id.0 > structure_id.0)
+                .or_else(|| ids.first())
+            {
+                *active_tool = ActiveTool::Wall(*next);
+            }
+            return;
+        }
+    }
+
+    *selected_slot = Some(slot);
+    *active_tool = hotbar_tool(slot, inventory);
+}
+
+/// Keeps the selected tool at the best tier available, e.g. right after buying an upgrade.
+fn refresh_active_tool_tier(
+    mut active_tool: ResMut<ActiveTool>,
+    inventories: Query<&Inventory, (With<ActionState<PlayerAction>>, Changed<Inventory>)>,
+) {
+    let Ok(inventory) = inventories.get_single() else {
+        return;
+    };
+    let ActiveTool::Item(ItemId::Tool { tool_id, tier }) = *active_tool else {
+        return;
+    };
+
+    let best_tier = inventory.best_tool_tier(tool_id);
+    if best_tier != tier {
         *active_tool = ActiveTool::Item(ItemId::Tool {
-            tool_id: ToolId::Hoe,
+            tool_id,
+            tier: best_tier,
         });
-    } else if action_state.just_pressed(&PlayerAction::Hotbar2) {
-        *active_tool = ActiveTool::Item(ItemId::Tool {
+    }
+}
+
+fn hotbar_tool(slot: usize, inventory: &Inventory) -> ActiveTool {
+    match slot {
+        0 => ActiveTool::Item(ItemId::Tool {
+            tool_id: ToolId::Hoe,
+            tier: inventory.best_tool_tier(ToolId::Hoe),
+        }),
+        1 => ActiveTool::Item(ItemId::Tool {
             tool_id: ToolId::Pickaxe,
-        });
-    } else if action_state.just_pressed(&PlayerAction::Hotbar3) {
-        *active_tool = ActiveTool::Item(ItemId::Tool {
+            tier: inventory.best_tool_tier(ToolId::Pickaxe),
+        }),
+        2 => ActiveTool::Item(ItemId::Tool {
             tool_id: ToolId::Scythe,
-        });
-    } else if action_state.just_pressed(&PlayerAction::Hotbar4) {
-        *active_tool = ActiveTool::Item(ItemId::Seed { crop_id: CropId(0) });
-    } else if action_state.just_pressed(&PlayerAction::Hotbar5) {
-        *active_tool = ActiveTool::Item(ItemId::Seed { crop_id: CropId(1) });
-    } else if action_state.just_pressed(&PlayerAction::Hotbar6) {
-        *active_tool = ActiveTool::Wall;
+            tier: inventory.best_tool_tier(ToolId::Scythe),
+        }),
+        3 => ActiveTool::Item(ItemId::Seed { crop_id: CropId(0) }),
+        4 => ActiveTool::Item(ItemId::Seed { crop_id: CropId(1) }),
+        5 => ActiveTool::Wall(EdgeStructureId(0)),
+        6 => ActiveTool::Item(ItemId::Prop { prop_id: PropId(0) }),
+        7 => ActiveTool::Item(ItemId::Prop { prop_id: PropId(1) }),
+        8 => ActiveTool::Door,
+        _ => ActiveTool::None,
     }
 }
 
 fn rotate_building(
     mut rotation: ResMut<BuildingRotation>,
     action_state: Query<&ActionState<PlayerAction>>,
+    camera: Query<&Transform, With<MainCameraParent>>,
 ) {
     let action_state = action_state.get_single();
     if action_state.is_err() {
@@ -104,25 +191,111 @@ fn rotate_building(
     }
     let action_state = action_state.unwrap();
     if action_state.just_pressed(&PlayerAction::Rotate) {
-        rotation.direction = match rotation.direction {
-            CardinalDirection::North => CardinalDirection::East,
-            CardinalDirection::East => CardinalDirection::South,
-            CardinalDirection::South => CardinalDirection::West,
-            CardinalDirection::West => CardinalDirection::North,
-        };
+        rotation.relative_direction = rotation.relative_direction.rotated_clockwise();
+    }
+
+    let yaw = camera.get_single().map_or(0.0, camera_yaw);
+    let direction = rotation.relative_direction.rotated_by_yaw(yaw);
+    if rotation.direction != direction {
+        rotation.direction = direction;
     }
 }
 
 #[derive(Resource)]
 pub struct BuildingRotation {
+    /// The rotation as seen from the camera, North being the side facing away from it.
+    pub relative_direction: CardinalDirection,
+    /// The rotation inside the world, updated whenever the camera or relative_direction changes.
     pub direction: CardinalDirection,
 }
 
+/// How far away from its own position an entity can interact with tiles.
+#[derive(Component)]
+pub struct Reach {
+    pub distance: f32,
+}
+
+impl Reach {
+    pub fn contains(&self, origin: Vec3, pos: &MapPos) -> bool {
+        (pos.world_pos(origin.y) - origin).length() <= self.distance
+    }
+}
+
+/// A line of walls which is currently being dragged out, but hasn't been built yet.
+#[derive(Resource, Debug)]
+pub struct WallPlan {
+    pub start: MapPos,
+    pub end: MapPos,
+}
+
+impl WallPlan {
+    /// Returns all tile edges covered by this plan.
+    /// Lines that aren't straight are turned into an L, running along x first and then along z.
+    pub fn edges(&self, rotation: CardinalDirection) -> Vec<(MapPos, CardinalDirection)> {
+        let start = self.start.global_tile_pos();
+        let end = self.end.global_tile_pos();
+        let delta = end - start;
+
+        if delta == IVec2::ZERO {
+            return vec![(self.start, rotation)];
+        }
+
+        // Straight lines respect the building rotation, L-shapes put their walls on the outside of the corner.
+        let horizontal_edge = if delta.y > 0 {
+            CardinalDirection::North
+        } else if delta.y < 0 {
+            CardinalDirection::South
+        } else if rotation == CardinalDirection::South {
+            CardinalDirection::South
+        } else {
+            CardinalDirection::North
+        };
+
+        let vertical_edge = if delta.x > 0 {
+            CardinalDirection::East
+        } else if delta.x < 0 {
+            CardinalDirection::West
+        } else if rotation == CardinalDirection::East {
+            CardinalDirection::East
+        } else {
+            CardinalDirection::West
+        };
+
+        let mut result = Vec::new();
+        if delta.x != 0 {
+            for x in range_inclusive(start.x, end.x) {
+                result.push((
+                    MapPos::from_global_tile_pos(IVec2::new(x, start.y)),
+                    horizontal_edge,
+                ));
+            }
+        }
+        if delta.y != 0 {
+            for y in range_inclusive(start.y, end.y) {
+                result.push((
+                    MapPos::from_global_tile_pos(IVec2::new(end.x, y)),
+                    vertical_edge,
+                ));
+            }
+        }
+
+        result
+    }
+}
+
+fn range_inclusive(from: i32, to: i32) -> impl Iterator<Item = i32> {
+    let step = if to >= from { 1 } else { -1 };
+    (0..=(to - from).abs()).map(move |i| from + i * step)
+}
+
 #[derive(Event, Debug)]
-struct TileInteractionEvent {
+pub struct TileInteractionEvent {
+    pub actor: Entity,
     pub pos: MapPos,
     pub rotation: CardinalDirection,
     pub used_tool: ActiveTool,
+    /// Whether the structure on the edge at rotation may be affected, e.g. demolished. False for area interactions.
+    pub targets_edge: bool,
 }
 
 #[derive(Event, Debug)]
@@ -130,17 +303,13 @@ struct CropDestroyedEvent {
     pub pos: MapPos,
 }
 
-#[derive(Event)]
-struct CropHarvestedEvent {
-    pub pos: MapPos,
-    pub crop_id: CropId,
-}
-
 fn detect_tile_interactions(
     active_tool: Res<ActiveTool>,
     building_rotation: Res<BuildingRotation>,
-    action_state: Query<&ActionState<PlayerAction>>,
+    action_state: Query<(Entity, &ActionState<PlayerAction>)>,
     tile_cursor: Query<&TileCursor>,
+    area_selection: Option<Res<AreaSelection>>,
+    mouse_cursor: Option<Res<MouseCursorOnTile>>,
     mut previously_interacted_tile: Local<Option<TilePos>>,
     mut tile_interaction_events: EventWriter<TileInteractionEvent>,
 ) {
@@ -149,7 +318,52 @@ fn detect_tile_interactions(
         error!("PlayerAction State was missing!");
         return;
     }
-    let action_state = action_state.unwrap();
+    let (actor, action_state) = action_state.unwrap();
+
+    if matches!(
+        *active_tool,
+        ActiveTool::Wall(_) | ActiveTool::CopyArea | ActiveTool::Blueprint
+    ) {
+        // Walls are placed through plan_walls, blueprints are handled in their own module
+        return;
+    }
+
+    // Empty hands and pickaxes interact with whichever edge is closest to the cursor, e.g. to open or demolish doors
+    let rotation = match (*active_tool, &mouse_cursor) {
+        (ActiveTool::None, Some(mouse_cursor))
+        | (
+            ActiveTool::Item(ItemId::Tool {
+                tool_id: ToolId::Pickaxe,
+                ..
+            }),
+            Some(mouse_cursor),
+        ) => mouse_cursor.tile_edge,
+        _ => building_rotation.direction,
+    };
+
+    if active_tool.uses_area_selection() {
+        // Area selections are applied all at once as soon as the button is released
+        if area_selection.is_some() && action_state.just_released(&PlayerAction::Interact) {
+            let positions: Vec<MapPos> = tile_cursor
+                .iter()
+                .filter(|cursor| cursor.in_reach)
+                .map(|cursor| cursor.pos)
+                .collect();
+            // The edge under the mouse only makes sense for a single tile, not for every tile in a pattern
+            let targets_edge = positions.len() == 1;
+
+            tile_interaction_events.send_batch(positions.into_iter().map(|pos| {
+                TileInteractionEvent {
+                    actor,
+                    pos,
+                    used_tool: *active_tool,
+                    rotation,
+                    targets_edge,
+                }
+            }));
+        }
+        return;
+    }
 
     if !action_state.pressed(&PlayerAction::Interact) {
         return;
@@ -160,6 +374,10 @@ fn detect_tile_interactions(
     }
 
     for cursor in tile_cursor.iter() {
+        if !cursor.in_reach {
+            continue;
+        }
+
         if let Some(previous) = *previously_interacted_tile {
             if previous == cursor.pos.tile {
                 return;
@@ -170,15 +388,77 @@ fn detect_tile_interactions(
             *previously_interacted_tile = Some(cursor.pos.tile);
         }
 
-        // TODO: in case we ever have regularly happening AoE interaction events, batch_send will be more performant
         tile_interaction_events.send(TileInteractionEvent {
+            actor,
             pos: cursor.pos.clone(),
             used_tool: active_tool.clone(),
-            rotation: building_rotation.direction,
+            rotation,
+            targets_edge: true,
         });
     }
 }
 
+fn plan_walls(
+    mut commands: Commands,
+    active_tool: Res<ActiveTool>,
+    building_rotation: Res<BuildingRotation>,
+    action_state: Query<(Entity, &ActionState<PlayerAction>, &Transform, &Reach)>,
+    tile_cursor: Query<&TileCursor>,
+    wall_plan: Option<ResMut<WallPlan>>,
+    mut tile_interaction_events: EventWriter<TileInteractionEvent>,
+) {
+    let action_state = action_state.get_single();
+    if action_state.is_err() {
+        error!("PlayerAction State was missing!");
+        return;
+    }
+    let (actor, action_state, transform, reach) = action_state.unwrap();
+
+    let Some(mut wall_plan) = wall_plan else {
+        if matches!(*active_tool, ActiveTool::Wall(_))
+            && action_state.just_pressed(&PlayerAction::Interact)
+        {
+            if let Some(cursor) = tile_cursor.iter().next() {
+                commands.insert_resource(WallPlan {
+                    start: cursor.pos,
+                    end: cursor.pos,
+                });
+            }
+        }
+        return;
+    };
+
+    if !matches!(*active_tool, ActiveTool::Wall(_))
+        || action_state.just_pressed(&PlayerAction::Cancel)
+    {
+        commands.remove_resource::<WallPlan>();
+        return;
+    }
+
+    if let Some(cursor) = tile_cursor.iter().next() {
+        if wall_plan.end != cursor.pos {
+            wall_plan.end = cursor.pos;
+        }
+    }
+
+    if !action_state.pressed(&PlayerAction::Interact) {
+        tile_interaction_events.send_batch(
+            wall_plan
+                .edges(building_rotation.direction)
+                .into_iter()
+                .filter(|(pos, _)| reach.contains(transform.translation, pos))
+                .map(|(pos, rotation)| TileInteractionEvent {
+                    actor,
+                    pos,
+                    rotation,
+                    used_tool: *active_tool,
+                    targets_edge: true,
+                }),
+        );
+        commands.remove_resource::<WallPlan>();
+    }
+}
+
 fn process_delete_crops(
     mut commands: Commands,
     mut world_data: ResMut<WorldData>,
@@ -202,35 +482,66 @@ fn process_delete_crops(
     }
 }
 
-fn process_harvested_crops(
-    mut commands: Commands,
-    mut harvested_crop_events: EventReader<CropHarvestedEvent>,
-    mut sprite_params: Sprite3dParams,
-    all_items: Res<AllItems>,
+/// Opens or closes the door on the interacted edge. Returns false if there is no door.
+fn toggle_door(
+    world_data: &mut WorldData,
+    wall_changed_events: &mut EventWriter<WallChangedEvent>,
+    recorded: &mut Vec<WorldCommand>,
+    event: &TileInteractionEvent,
+) -> bool {
+    let Some((pos, edge, EdgeStructure::Door { is_open })) =
+        find_edge_structure(world_data, event.pos, event.rotation)
+    else {
+        return false;
+    };
+    let Some(chunk) = world_data.chunks.get_mut(&pos.chunk) else {
+        return false;
+    };
+
+    chunk
+        .at_pos_mut(&pos.tile)
+        .walls
+        .set_at(edge, Some(EdgeStructure::Door { is_open: !is_open }));
+    wall_changed_events.send(WallChangedEvent {
+        pos,
+        edge,
+        is_door_toggle: true,
+    });
+    recorded.push(WorldCommand::SetEdge {
+        pos,
+        edge,
+        before: Some(EdgeStructure::Door { is_open }),
+        after: Some(EdgeStructure::Door { is_open: !is_open }),
+    });
+    true
+}
+
+fn record_payment(recorded: &mut Vec<WorldCommand>, actor: Entity, cost: &BuildCost) {
+    recorded.extend(
+        cost.items()
+            .map(|(item_id, amount)| WorldCommand::ChangeInventory {
+                actor,
+                item_id,
+                delta: -(amount as i64),
+            }),
+    );
+}
+
+fn record_refund(
+    recorded: &mut Vec<WorldCommand>,
+    actor: Entity,
+    refund: Vec<(ChunkPos, DropId, DropData)>,
 ) {
-    for event in harvested_crop_events.read() {
-        // TODO: Consider bunching up nearby same-Item drops into one bigger drop.
-        // TODO: If chunk is not loaded, just add the Item to whomever caused the interaction immediately if nearby
-        // TODO: (premature) Drops should probably be persisted inside the chunk they're in and get (de-)spawned accordingly, otherwise 1000+ drops somewhere in the middle of nowhere might cause performance issues?
-        // Also, if an NPC with Inventory walks through that chunk (maybe a bit further away from players than chunk loading distance so they won't notice as easily), they automagically pick it up?
-
-        if let Some(crop) = all_items.crops.get(&event.crop_id) {
-            commands.spawn((
-                Name::new("Drop"),
-                Sprite3d {
-                    transform: Transform::from_translation(event.pos.world_pos(0.0)),
-                    image: crop.harvested_sprite.clone(),
-                    pixels_per_metre: SPRITE_PIXELS_PER_METER,
-                    pivot: SPRITE_DEFAULT_PIVOT,
-                    ..default()
-                }
-                .bundle(&mut sprite_params),
-                ItemDrop::from_crop(event.crop_id, 1),
-            ));
-        } else {
-            error!("Unable to find crop with id {}", event.crop_id.0)
-        }
-    }
+    recorded.extend(
+        refund
+            .into_iter()
+            .map(|(chunk, id, drop)| WorldCommand::AddDrop {
+                actor,
+                chunk,
+                id,
+                drop,
+            }),
+    );
 }
 
 fn process_tile_interactions(
@@ -238,29 +549,67 @@ fn process_tile_interactions(
     mut commands: Commands,
     mut update_tile_events: EventWriter<UpdateTileEvent>,
     mut destroy_crop_events: EventWriter<CropDestroyedEvent>,
-    mut harvest_crop_events: EventWriter<CropHarvestedEvent>,
+    mut wall_changed_events: EventWriter<WallChangedEvent>,
     mut world_data: ResMut<WorldData>,
     mut loaded_chunk_data: ResMut<LoadedChunks>,
+    mut inventories: Query<&mut Inventory>,
+    mut build_failed_events: EventWriter<BuildFailedEvent>,
     simulation_time: Res<SimulationTime>,
     all_items: Res<AllItems>,
-    mut sprite_params: Sprite3dParams,
-    debug_materials: Res<DebugMaterials>,
-    debug_meshes: Res<DebugMeshes>,
+    mut drop_params: DropSpawnParams,
+    mut undo_history: ResMut<UndoHistory>,
 ) {
+    // Everything happening in the same frame is undone at once, e.g. a whole line of walls
+    let mut recorded = Vec::new();
+
     for event in tile_interaction_event.read() {
         match event.used_tool {
-            ActiveTool::None => {}
-            ActiveTool::Wall => {
-                // TODO: Drag Planning
-                // TODO: -> Ability to cancel placement
-
-                let chunk = world_data.chunks.get_mut(&event.pos.chunk).unwrap();
-                let tile = chunk.at_pos_mut(&event.pos.tile);
-                if tile.walls.at(event.rotation) {
+            ActiveTool::None => {
+                toggle_door(
+                    &mut world_data,
+                    &mut wall_changed_events,
+                    &mut recorded,
+                    event,
+                );
+            }
+            ActiveTool::Wall(structure_id) => {
+                if find_edge_structure(&world_data, event.pos, event.rotation).is_some() {
                     continue;
                 }
+                let Some(definition) = all_items.edge_structures.get(&structure_id) else {
+                    error!("Unable to find edge structure with id {}", structure_id.0);
+                    continue;
+                };
+                let Some(chunk) = world_data.chunks.get_mut(&event.pos.chunk) else {
+                    continue;
+                };
+                let Ok(mut inventory) = inventories.get_mut(event.actor) else {
+                    continue;
+                };
+                if !inventory.pay(&definition.build_cost) {
+                    build_failed_events.send(BuildFailedEvent {
+                        pos: event.pos,
+                        missing: inventory.missing_items(&definition.build_cost),
+                    });
+                    continue;
+                }
+                record_payment(&mut recorded, event.actor, &definition.build_cost);
 
-                tile.walls.set_at(event.rotation, true);
+                chunk
+                    .at_pos_mut(&event.pos.tile)
+                    .walls
+                    .set_at(event.rotation, Some(EdgeStructure::Wall { structure_id }));
+                recorded.push(WorldCommand::SetEdge {
+                    pos: event.pos,
+                    edge: event.rotation,
+                    before: None,
+                    after: Some(EdgeStructure::Wall { structure_id }),
+                });
+                wall_changed_events.send(WallChangedEvent {
+                    pos: event.pos,
+                    edge: event.rotation,
+                    is_door_toggle: false,
+                });
 
                 // TODO: Move graphic this in an event
                 if let Some(loaded_data) = loaded_chunk_data.chunks.get_mut(&event.pos.chunk) {
@@ -268,9 +617,68 @@ fn process_tile_interactions(
                         build_and_spawn_wall_entity(
                             &mut commands,
                             tile,
+                            event.pos,
+                            event.rotation,
+                            definition,
+                        );
+                    }
+                }
+            }
+            ActiveTool::CopyArea | ActiveTool::Blueprint => {}
+            ActiveTool::Door => {
+                if toggle_door(
+                    &mut world_data,
+                    &mut wall_changed_events,
+                    &mut recorded,
+                    event,
+                ) {
+                    continue;
+                }
+                if find_edge_structure(&world_data, event.pos, event.rotation).is_some() {
+                    continue;
+                }
+                let Some(chunk) = world_data.chunks.get_mut(&event.pos.chunk) else {
+                    continue;
+                };
+                let Ok(mut inventory) = inventories.get_mut(event.actor) else {
+                    continue;
+                };
+                let build_cost = &all_items.door.build_cost;
+                if !inventory.pay(build_cost) {
+                    build_failed_events.send(BuildFailedEvent {
+                        pos: event.pos,
+                        missing: inventory.missing_items(build_cost),
+                    });
+                    continue;
+                }
+                record_payment(&mut recorded, event.actor, build_cost);
+
+                chunk
+                    .at_pos_mut(&event.pos.tile)
+                    .walls
+                    .set_at(event.rotation, Some(EdgeStructure::Door { is_open: false }));
+                recorded.push(WorldCommand::SetEdge {
+                    pos: event.pos,
+                    edge: event.rotation,
+                    before: None,
+                    after: Some(EdgeStructure::Door { is_open: false }),
+                });
+                wall_changed_events.send(WallChangedEvent {
+                    pos: event.pos,
+                    edge: event.rotation,
+                    is_door_toggle: false,
+                });
+
+                if let Some(loaded_data) = loaded_chunk_data.chunks.get_mut(&event.pos.chunk) {
+                    if let Some(tile) = loaded_data.get_tile(event.pos.tile.x, event.pos.tile.y) {
+                        build_and_spawn_door_entity(
+                            &mut commands,
+                            tile,
+                            event.pos,
                             event.rotation,
-                            &debug_meshes,
-                            &debug_materials,
+                            false,
+                            &drop_params.meshes,
+                            &drop_params.materials,
                         );
                     }
                 }
@@ -278,62 +686,156 @@ fn process_tile_interactions(
             ActiveTool::Item(item) => {
                 match item {
                     ItemId::Crop { .. } => {
-                        // ignore. Might wanna eat or give the Item to entities on that tile in the future.
+                        // Might wanna eat or give the Item to other entities on that tile in the future.
+                        let Some(chunk) = world_data.chunks.get_mut(&event.pos.chunk) else {
+                            continue;
+                        };
+                        let Some(prop) = chunk.props.get_mut(&event.pos.tile) else {
+                            continue;
+                        };
+                        let prop_before = prop.clone();
+                        let Some(bin_inventory) = prop.inventory.as_mut() else {
+                            continue;
+                        };
+
+                        let Ok(mut inventory) = inventories.get_mut(event.actor) else {
+                            continue;
+                        };
+
+                        let amount = inventory.take_all(&item);
+                        if amount > 0 {
+                            bin_inventory.add_item(&item, amount);
+                            recorded.push(WorldCommand::ChangeInventory {
+                                actor: event.actor,
+                                item_id: item,
+                                delta: -(amount as i64),
+                            });
+                            recorded.push(WorldCommand::SetProp {
+                                pos: event.pos,
+                                before: Some(prop_before),
+                                after: Some(prop.clone()),
+                            });
+                        }
                     }
-                    ItemId::Prop { .. } => {
-                        // TODO: Attempt to place the prop
+                    ItemId::Prop { prop_id } => {
+                        let Some(chunk) = world_data.chunks.get_mut(&event.pos.chunk) else {
+                            continue;
+                        };
+                        if chunk.props.contains_key(&event.pos.tile)
+                            || chunk.crops.contains_key(&event.pos.tile)
+                        {
+                            continue;
+                        }
+
+                        let Some(prop_definition) = all_items.props.get(&prop_id) else {
+                            error!("Unable to find prop with id {}", prop_id.0);
+                            continue;
+                        };
+
+                        let Ok(mut inventory) = inventories.get_mut(event.actor) else {
+                            continue;
+                        };
+                        if !inventory.pay(&prop_definition.build_cost) {
+                            build_failed_events.send(BuildFailedEvent {
+                                pos: event.pos,
+                                missing: inventory.missing_items(&prop_definition.build_cost),
+                            });
+                            continue;
+                        }
+                        record_payment(&mut recorded, event.actor, &prop_definition.build_cost);
+
+                        let prop = PropData::new(prop_definition);
+                        recorded.push(WorldCommand::SetProp {
+                            pos: event.pos,
+                            before: None,
+                            after: Some(prop.clone()),
+                        });
+                        chunk.props.insert(event.pos.tile, prop);
+
+                        if let Some(loaded_data) =
+                            loaded_chunk_data.chunks.get_mut(&event.pos.chunk)
+                        {
+                            let entity = spawn_prop_entity(
+                                &mut commands,
+                                loaded_data.chunk_parent,
+                                &event.pos,
+                                prop_definition,
+                            );
+                            loaded_data.props.insert(event.pos.tile, entity);
+                        }
                     }
                     ItemId::Seed { crop_id } => {
-                        let chunk = world_data.chunks.get_mut(&event.pos.chunk).unwrap();
-                        if !chunk.at_pos(&event.pos.tile).is_tilled {
+                        let Some(chunk) = world_data.chunks.get_mut(&event.pos.chunk) else {
+                            continue;
+                        };
+                        let tile = chunk.at_pos(&event.pos.tile);
+                        if !tile.is_tilled || tile.has_weeds {
+                            continue;
+                        }
+
+                        if chunk.crops.get(&event.pos.tile).is_some()
+                            || chunk.props.get(&event.pos.tile).is_some()
+                        {
                             continue;
                         }
 
-                        if chunk.crops.get(&event.pos.tile).is_some() {
+                        let Ok(mut inventory) = inventories.get_mut(event.actor) else {
+                            continue;
+                        };
+
+                        if !inventory.remove_item(&item, 1) {
                             continue;
                         }
 
                         let crop_definition = all_items.crops.get(&crop_id).unwrap();
-                        chunk.crops.insert(
-                            event.pos.tile,
-                            CropData::new(&crop_definition, &simulation_time),
-                        );
+                        let crop = CropData::new(&crop_definition, &simulation_time);
+                        recorded.push(WorldCommand::ChangeInventory {
+                            actor: event.actor,
+                            item_id: item,
+                            delta: -1,
+                        });
+                        recorded.push(WorldCommand::SetCrop {
+                            pos: event.pos,
+                            before: None,
+                            after: Some(crop.clone()),
+                            recorded_at: simulation_time.now(),
+                        });
+                        chunk.crops.insert(event.pos.tile, crop);
 
                         // TODO: Event - Plant Seed
                         if let Some(loaded_data) =
                             loaded_chunk_data.chunks.get_mut(&event.pos.chunk)
                         {
-                            let entity = commands
-                                .spawn((
-                                    Name::new("Plant"),
-                                    AtlasSprite3d {
-                                        atlas: crop_definition.texture_atlas.clone(),
-                                        index: 0,
-                                        transform: Transform::from_translation(
-                                            event.pos.pos_inside_chunk(0.0)
-                                                - Vec3::new(0.0, 0.0, -0.1),
-                                        ),
-                                        pixels_per_metre: SPRITE_PIXELS_PER_METER,
-                                        pivot: SPRITE_DEFAULT_PIVOT,
-                                        ..default()
-                                    }
-                                    .bundle(&mut sprite_params),
-                                ))
-                                .set_parent(loaded_data.chunk_parent)
-                                .id();
+                            let entity = spawn_crop_entity(
+                                &mut commands,
+                                &mut drop_params.sprite_params,
+                                loaded_data.chunk_parent,
+                                &event.pos,
+                                crop_definition,
+                                0,
+                            );
 
                             loaded_data.crops.insert(event.pos.tile, entity);
                         }
                     }
-                    ItemId::Tool { tool_id } => match tool_id {
+                    ItemId::Tool { tool_id, .. } => match tool_id {
                         ToolId::Hoe => {
                             let world_data = &mut *world_data;
-                            let chunk = world_data.chunks.get_mut(&event.pos.chunk).unwrap();
-                            if chunk.at_pos(&event.pos.tile).is_tilled {
+                            let Some(chunk) = world_data.chunks.get_mut(&event.pos.chunk) else {
+                                continue;
+                            };
+                            if chunk.at_pos(&event.pos.tile).is_tilled
+                                || chunk.props.contains_key(&event.pos.tile)
+                            {
                                 continue;
                             }
 
                             chunk.set_at_pos(&event.pos.tile, true);
+                            recorded.push(WorldCommand::SetTilled {
+                                pos: event.pos,
+                                before: false,
+                                after: true,
+                            });
 
                             // TODO: Event - Place Floor tile
                             if loaded_chunk_data.chunks.contains_key(&event.pos.chunk) {
@@ -346,45 +848,199 @@ fn process_tile_interactions(
                             }
                         }
                         ToolId::Pickaxe => {
-                            let chunk = world_data.chunks.get_mut(&event.pos.chunk).unwrap();
-                            if !chunk.at_pos(&event.pos.tile).is_tilled {
+                            let targeted_edge = event
+                                .targets_edge
+                                .then(|| {
+                                    find_edge_structure(&world_data, event.pos, event.rotation)
+                                })
+                                .flatten();
+                            let Some(chunk) = world_data.chunks.get_mut(&event.pos.chunk) else {
                                 continue;
-                            }
+                            };
 
-                            if let Some(_) = chunk.crops.get(&event.pos.tile) {
+                            if let Some(crop) = chunk.crops.get(&event.pos.tile) {
+                                recorded.push(WorldCommand::SetCrop {
+                                    pos: event.pos,
+                                    before: Some(crop.clone()),
+                                    after: None,
+                                    recorded_at: simulation_time.now(),
+                                });
                                 destroy_crop_events.send(CropDestroyedEvent { pos: event.pos });
                                 continue;
                             }
 
-                            // TODO: Event - Remove tilled tile
-                            chunk.set_at_pos(&event.pos.tile, false);
-                            if loaded_chunk_data.chunks.contains_key(&event.pos.chunk) {
-                                update_tile_events
-                                    .send(UpdateTileEvent::new(event.pos.chunk, event.pos.tile));
-                                update_tile_events.send_batch(UpdateTileEvent::surrounding_tiles(
-                                    event.pos.chunk,
-                                    event.pos.tile,
-                                ));
+                            if let Some(prop) = chunk.props.get(&event.pos.tile) {
+                                // Don't destroy whatever has been put into the prop
+                                if prop
+                                    .inventory
+                                    .as_ref()
+                                    .map_or(false, |inventory| !inventory.is_empty())
+                                {
+                                    continue;
+                                }
+
+                                let prop_id = prop.prop_id;
+                                recorded.push(WorldCommand::SetProp {
+                                    pos: event.pos,
+                                    before: chunk.props.remove(&event.pos.tile),
+                                    after: None,
+                                });
+                                if let Some(entity) =
+                                    loaded_chunk_data.chunks.get_mut(&event.pos.chunk).and_then(
+                                        |loaded_data| loaded_data.props.remove(&event.pos.tile),
+                                    )
+                                {
+                                    commands.entity(entity).despawn_recursive();
+                                }
+
+                                if let Some(definition) = all_items.props.get(&prop_id) {
+                                    let refund = drop_demolition_refund(
+                                        &mut commands,
+                                        &mut drop_params,
+                                        &mut world_data,
+                                        &mut loaded_chunk_data,
+                                        &definition.build_cost,
+                                        event.pos,
+                                    );
+                                    record_refund(&mut recorded, event.actor, refund);
+                                }
+                                continue;
+                            }
+
+                            // Targeted walls come before the soil they are standing on
+                            let Some((pos, edge, structure)) = targeted_edge else {
+                                if chunk.at_pos(&event.pos.tile).is_tilled {
+                                    // Weeds go away together with the soil
+                                    if chunk.at_pos(&event.pos.tile).has_weeds {
+                                        recorded.push(WorldCommand::SetWeeds {
+                                            pos: event.pos,
+                                            before: true,
+                                            after: false,
+                                        });
+                                    }
+
+                                    // TODO: Event - Remove tilled tile
+                                    chunk.set_at_pos(&event.pos.tile, false);
+                                    recorded.push(WorldCommand::SetTilled {
+                                        pos: event.pos,
+                                        before: true,
+                                        after: false,
+                                    });
+                                    if loaded_chunk_data.chunks.contains_key(&event.pos.chunk) {
+                                        update_tile_events.send(UpdateTileEvent::new(
+                                            event.pos.chunk,
+                                            event.pos.tile,
+                                        ));
+                                        update_tile_events.send_batch(
+                                            UpdateTileEvent::surrounding_tiles(
+                                                event.pos.chunk,
+                                                event.pos.tile,
+                                            ),
+                                        );
+                                    }
+                                }
+                                continue;
+                            };
+                            let Some(chunk) = world_data.chunks.get_mut(&pos.chunk) else {
+                                continue;
+                            };
+
+                            chunk.at_pos_mut(&pos.tile).walls.set_at(edge, None);
+                            wall_changed_events.send(WallChangedEvent {
+                                pos,
+                                edge,
+                                is_door_toggle: false,
+                            });
+                            recorded.push(WorldCommand::SetEdge {
+                                pos,
+                                edge,
+                                before: Some(structure),
+                                after: None,
+                            });
+
+                            if let Some(build_cost) = structure.build_cost(&all_items) {
+                                let refund = drop_demolition_refund(
+                                    &mut commands,
+                                    &mut drop_params,
+                                    &mut world_data,
+                                    &mut loaded_chunk_data,
+                                    build_cost,
+                                    pos,
+                                );
+                                record_refund(&mut recorded, event.actor, refund);
                             }
                         }
                         ToolId::Scythe => {
-                            let chunk = world_data.chunks.get_mut(&event.pos.chunk).unwrap();
+                            let Some(chunk) = world_data.chunks.get_mut(&event.pos.chunk) else {
+                                continue;
+                            };
 
                             if let Some(crop) = chunk.crops.get(&event.pos.tile) {
                                 if crop.stage + 1
-                                    >= all_items.crops.get(&crop.crop_id).unwrap().stages
+                                    < all_items.crops.get(&crop.crop_id).unwrap().stages
                                 {
-                                    harvest_crop_events.send(CropHarvestedEvent {
-                                        pos: event.pos,
+                                    continue;
+                                }
+
+                                let crop = crop.clone();
+                                recorded.push(WorldCommand::SetCrop {
+                                    pos: event.pos,
+                                    before: Some(crop.clone()),
+                                    after: None,
+                                    recorded_at: simulation_time.now(),
+                                });
+                                destroy_crop_events.send(CropDestroyedEvent { pos: event.pos });
+
+                                // TODO: If chunk is not loaded, just add the Item to whomever caused the interaction immediately if nearby
+                                let drop = DropData {
+                                    item_id: ItemId::Crop {
                                         crop_id: crop.crop_id,
+                                    },
+                                    amount: 1,
+                                    position: event.pos.world_pos(0.0),
+                                    pickup_blocked_until: SimulationTick::default(),
+                                };
+                                if let Some(
+                                    AddedDrop::New(chunk, id) | AddedDrop::Merged(chunk, id),
+                                ) = add_drop_to_world(
+                                    &mut commands,
+                                    &mut drop_params,
+                                    &mut world_data,
+                                    &mut loaded_chunk_data,
+                                    drop.clone(),
+                                ) {
+                                    recorded.push(WorldCommand::AddDrop {
+                                        actor: event.actor,
+                                        chunk,
+                                        id,
+                                        drop,
                                     });
-                                    destroy_crop_events.send(CropDestroyedEvent { pos: event.pos });
+                                }
+                                continue;
+                            }
+
+                            let tile = chunk.at_pos_mut(&event.pos.tile);
+                            if tile.has_weeds {
+                                tile.has_weeds = false;
+                                recorded.push(WorldCommand::SetWeeds {
+                                    pos: event.pos,
+                                    before: true,
+                                    after: false,
+                                });
+                                if loaded_chunk_data.chunks.contains_key(&event.pos.chunk) {
+                                    update_tile_events.send(UpdateTileEvent::new(
+                                        event.pos.chunk,
+                                        event.pos.tile,
+                                    ));
                                 }
                             }
                         }
                     },
+                    ItemId::Resource { .. } => {}
                 }
             }
         }
     }
+
+    undo_history.record(recorded);
 }
@@ -1,13 +1,18 @@
 use bevy::app::App;
 use bevy::prelude::{
     in_state, Commands, DespawnRecursiveExt, Entity, IntoSystemConfigs, Local, Plugin, Query, Res,
-    Update,
+    Transform, Update, With,
 };
 use bevy::utils::HashMap;
 
-use crate::game::walls::build_and_spawn_wall_entity_with_mesh_and_material;
+use data::prelude::AllItems;
+
+use crate::game::player::ControlledByPlayer;
+use crate::game::walls::{
+    build_and_spawn_door_preview, build_and_spawn_wall_entity_with_mesh_and_material,
+};
 use crate::load::{DebugMaterials, DebugMeshes};
-use crate::prelude::interaction::BuildingRotation;
+use crate::prelude::interaction::{BuildingRotation, Reach, WallPlan};
 use crate::prelude::loaded_chunks::LoadedChunks;
 use crate::prelude::tile_cursor::TileCursor;
 use crate::prelude::{ActiveTool, CardinalDirection, MapPos};
@@ -20,15 +25,13 @@ impl Plugin for InteractionPreviewPlugin {
     }
 }
 
-struct LastFramePreviewDataCell {
-    rotation: CardinalDirection,
-    tool: ActiveTool,
-    preview_entity: Entity,
-}
+/// A previewed edge and whether it is within reach.
+type PreviewKey = (MapPos, CardinalDirection, bool);
 
 #[derive(Default)]
 struct LastFramePreviewData {
-    previews: HashMap<MapPos, LastFramePreviewDataCell>,
+    previews: HashMap<PreviewKey, Entity>,
+    tool: ActiveTool,
 }
 
 fn update_preview(
@@ -36,69 +39,105 @@ fn update_preview(
     loaded_chunk_data: Res<LoadedChunks>,
     active_tool: Res<ActiveTool>,
     building_rotation: Res<BuildingRotation>,
+    wall_plan: Option<Res<WallPlan>>,
     cursor_query: Query<&TileCursor>,
+    player: Query<(&Transform, &Reach), With<ControlledByPlayer>>,
     debug_materials: Res<DebugMaterials>,
     debug_meshes: Res<DebugMeshes>,
+    all_items: Res<AllItems>,
     mut last_frame_preview_data: Local<LastFramePreviewData>,
 ) {
-    let mut still_highlighted_positions = Vec::new();
-    for cursor in cursor_query.iter() {
-        still_highlighted_positions.push(&cursor.pos);
-        if let Some(existing_preview) = last_frame_preview_data.previews.get(&cursor.pos) {
-            if existing_preview.tool == *active_tool
-                && existing_preview.rotation == building_rotation.direction
-            {
-                continue;
+    let highlighted_walls: Vec<PreviewKey> = match *active_tool {
+        ActiveTool::None => Vec::new(),
+        ActiveTool::Item(_) => Vec::new(),
+        // Blueprints spawn their own previews
+        ActiveTool::CopyArea | ActiveTool::Blueprint => Vec::new(),
+        ActiveTool::Door => cursor_query
+            .iter()
+            .map(|cursor| (cursor.pos, building_rotation.direction, cursor.in_reach))
+            .collect(),
+        ActiveTool::Wall(_) => {
+            if let Some(wall_plan) = wall_plan {
+                // Edges out of reach are skipped once the plan gets built
+                let player = player.get_single().ok();
+                wall_plan
+                    .edges(building_rotation.direction)
+                    .into_iter()
+                    .map(|(pos, rotation)| {
+                        let in_reach = player.map_or(false, |(transform, reach)| {
+                            reach.contains(transform.translation, &pos)
+                        });
+                        (pos, rotation, in_reach)
+                    })
+                    .collect()
             } else {
-                commands
-                    .entity(existing_preview.preview_entity)
-                    .despawn_recursive();
-                last_frame_preview_data.previews.remove(&cursor.pos);
+                cursor_query
+                    .iter()
+                    .map(|cursor| (cursor.pos, building_rotation.direction, cursor.in_reach))
+                    .collect()
             }
         }
+    };
 
-        let tile = {
-            if let Some(loaded_data) = loaded_chunk_data.chunks.get(&cursor.pos.chunk) {
-                if let Some(tile) = loaded_data.get_tile(cursor.pos.tile.x, cursor.pos.tile.y) {
-                    tile
-                } else {
-                    continue;
-                }
-            } else {
-                continue;
-            }
+    if last_frame_preview_data.tool != *active_tool {
+        for (_key, entity) in last_frame_preview_data.previews.drain() {
+            commands.entity(entity).despawn_recursive();
+        }
+        last_frame_preview_data.tool = *active_tool;
+    }
+
+    for key in highlighted_walls.iter() {
+        let (pos, rotation, in_reach) = key;
+        if last_frame_preview_data.previews.contains_key(key) {
+            continue;
+        }
+
+        let material = if *in_reach {
+            debug_materials.preview_ghost.clone()
+        } else {
+            debug_materials.out_of_reach_cursor.clone()
+        };
+
+        let Some(loaded_data) = loaded_chunk_data.chunks.get(&pos.chunk) else {
+            continue;
+        };
+        let Some(tile) = loaded_data.get_tile(pos.tile.x, pos.tile.y) else {
+            continue;
         };
 
-        match *active_tool {
-            ActiveTool::None => {}
-            ActiveTool::Item(_) => {}
-            ActiveTool::Wall => {
-                let entity = build_and_spawn_wall_entity_with_mesh_and_material(
+        let entity = match *active_tool {
+            ActiveTool::Wall(structure_id) => {
+                let Some(definition) = all_items.edge_structures.get(&structure_id) else {
+                    continue;
+                };
+                build_and_spawn_wall_entity_with_mesh_and_material(
                     &mut commands,
                     tile,
-                    building_rotation.direction,
-                    debug_meshes.wall.clone(),
-                    debug_materials.preview_ghost.clone(),
-                );
-
-                last_frame_preview_data.previews.insert(
-                    cursor.pos,
-                    LastFramePreviewDataCell {
-                        rotation: building_rotation.direction,
-                        tool: active_tool.clone(),
-                        preview_entity: entity,
-                    },
-                );
+                    *rotation,
+                    definition,
+                    material,
+                )
             }
-        }
+            ActiveTool::Door => build_and_spawn_door_preview(
+                &mut commands,
+                tile,
+                *rotation,
+                &debug_meshes,
+                material,
+            ),
+            ActiveTool::None
+            | ActiveTool::Item(_)
+            | ActiveTool::CopyArea
+            | ActiveTool::Blueprint => continue,
+        };
+
+        last_frame_preview_data.previews.insert(*key, entity);
     }
 
-    for (_pos, obsolete_cell) in last_frame_preview_data
+    for (_key, obsolete_entity) in last_frame_preview_data
         .previews
-        .extract_if(|pos, _value| !still_highlighted_positions.contains(&pos))
+        .extract_if(|key, _value| !highlighted_walls.contains(key))
     {
-        commands
-            .entity(obsolete_cell.preview_entity)
-            .despawn_recursive();
+        commands.entity(obsolete_entity).despawn_recursive();
     }
 }
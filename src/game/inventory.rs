@@ -1,9 +1,9 @@
 use bevy::prelude::Component;
 use bevy::utils::{hashbrown, HashMap};
 
-use data::prelude::ItemId;
+use data::prelude::{BuildCost, ItemId, ToolId, ToolTier};
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, PartialEq)]
 pub struct Inventory {
     items: HashMap<ItemId, u32>,
 }
@@ -30,11 +30,79 @@ impl Inventory {
         }
     }
 
-    pub fn item_count(self, item_id: ItemId) -> u32 {
+    /// Removes `amount` of the given item. Returns false and leaves the inventory untouched if there aren't enough.
+    pub fn remove_item(&mut self, item_id: &ItemId, amount: u32) -> bool {
+        let Some(count) = self.items.get_mut(item_id) else {
+            return amount == 0;
+        };
+
+        if *count < amount {
+            return false;
+        }
+
+        *count -= amount;
+        if *count == 0 {
+            self.items.remove(item_id);
+        }
+
+        true
+    }
+
+    /// Removes everything listed in the cost. Returns false and leaves the inventory untouched if anything is missing.
+    pub fn pay(&mut self, cost: &BuildCost) -> bool {
+        if !self.missing_items(cost).is_empty() {
+            return false;
+        }
+
+        for (item_id, amount) in cost.items() {
+            self.remove_item(&item_id, amount);
+        }
+
+        true
+    }
+
+    /// Lists how many of each item are still needed to pay for the cost.
+    pub fn missing_items(&self, cost: &BuildCost) -> Vec<(ItemId, u32)> {
+        cost.items()
+            .filter_map(|(item_id, amount)| {
+                let count = self.item_count(item_id);
+                (count < amount).then(|| (item_id, amount - count))
+            })
+            .collect()
+    }
+
+    /// Removes the whole stack of the given item and returns how many were in it.
+    pub fn take_all(&mut self, item_id: &ItemId) -> u32 {
+        self.items.remove(item_id).unwrap_or(0)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn item_count(&self, item_id: ItemId) -> u32 {
         if let Some(count) = self.items.get(&item_id) {
             count.clone()
         } else {
             0
         }
     }
+
+    /// Returns the highest tier of the given tool inside this inventory. Basic tools are always available.
+    pub fn best_tool_tier(&self, tool_id: ToolId) -> ToolTier {
+        let mut best_tier = ToolTier::Basic;
+        let mut tier = ToolTier::Basic;
+        while let Some(next) = tier.next() {
+            if self.item_count(ItemId::Tool {
+                tool_id,
+                tier: next,
+            }) > 0
+            {
+                best_tier = next;
+            }
+            tier = next;
+        }
+
+        best_tier
+    }
 }
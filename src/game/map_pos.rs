@@ -1,4 +1,4 @@
-use bevy::math::Vec3;
+use bevy::math::{IVec2, Vec3};
 
 use crate::game::CHUNK_SIZE;
 use crate::prelude::{ChunkPos, TilePos};
@@ -17,6 +17,30 @@ impl MapPos {
         }
     }
 
+    /// The tile containing the given world position. Tile origins are at their center.
+    pub fn from_world_pos(pos: Vec3) -> Self {
+        Self::from_global_tile_pos(IVec2::new(pos.x.round() as i32, pos.z.round() as i32))
+    }
+
+    pub fn from_global_tile_pos(pos: IVec2) -> Self {
+        let chunk_size = CHUNK_SIZE as i32;
+
+        MapPos {
+            chunk: ChunkPos::new(pos.x.div_euclid(chunk_size), pos.y.div_euclid(chunk_size)),
+            tile: TilePos::new(
+                pos.x.rem_euclid(chunk_size) as u32,
+                pos.y.rem_euclid(chunk_size) as u32,
+            ),
+        }
+    }
+
+    pub fn global_tile_pos(&self) -> IVec2 {
+        IVec2::new(
+            self.chunk.x * CHUNK_SIZE as i32 + self.tile.x as i32,
+            self.chunk.y * CHUNK_SIZE as i32 + self.tile.y as i32,
+        )
+    }
+
     pub fn world_pos(&self, y: f32) -> Vec3 {
         Vec3 {
             x: self.chunk.x as f32 * CHUNK_SIZE as f32 + self.tile.x as f32,
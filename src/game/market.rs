@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::{
+    in_state, on_event, EventReader, IntoSystemConfigs, OnEnter, Res, ResMut, Resource,
+};
+use bevy::utils::HashMap;
+use bevy_egui::egui::{Align2, Pos2};
+use bevy_egui::{egui, EguiContexts};
+use rand::Rng;
+
+use data::prelude::{AllItems, ItemId, Season};
+
+use crate::game::economy::{pay_out_shipping_bins, sell_price};
+use crate::prelude::simulation_time::DayStartedEvent;
+use crate::prelude::{GameState, SimulationDate};
+
+/// How far a price may drift away from its base price due to random daily fluctuations.
+const MAX_DRIFT: f32 = 0.25;
+const DAILY_DRIFT_STEP: f32 = 0.05;
+/// How much of the sold supply is still remembered by the market on the next day.
+const SUPPLY_RETENTION: f32 = 0.7;
+/// How many sold items it takes to halve the price.
+const SUPPLY_FOR_HALF_PRICE: f32 = 50.0;
+const IN_SEASON_MULTIPLIER: f32 = 0.8;
+const OUT_OF_SEASON_MULTIPLIER: f32 = 1.3;
+const PRICE_HISTORY_LENGTH: usize = 28;
+
+pub struct MarketPlugin;
+impl Plugin for MarketPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Market>()
+            .add_systems(OnEnter(GameState::Playing), initialize_market)
+            .add_systems(
+                Update,
+                (
+                    update_market_prices
+                        .after(pay_out_shipping_bins)
+                        .run_if(on_event::<DayStartedEvent>()),
+                    market_ui,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+struct MarketEntry {
+    base_price: u32,
+    seasons: Vec<Season>,
+    drift: f32,
+    supply: f32,
+    current_price: u32,
+    history: VecDeque<u32>,
+}
+
+impl MarketEntry {
+    fn new(base_price: u32, seasons: Vec<Season>) -> Self {
+        Self {
+            base_price,
+            seasons,
+            drift: 0.0,
+            supply: 0.0,
+            current_price: base_price,
+            history: VecDeque::from([base_price]),
+        }
+    }
+
+    fn calculate_price(&self, season: Season) -> u32 {
+        let season_multiplier = if self.seasons.contains(&season) {
+            IN_SEASON_MULTIPLIER
+        } else {
+            OUT_OF_SEASON_MULTIPLIER
+        };
+        let supply_multiplier = 1.0 / (1.0 + self.supply / SUPPLY_FOR_HALF_PRICE);
+
+        let price =
+            self.base_price as f32 * (1.0 + self.drift) * season_multiplier * supply_multiplier;
+
+        price.round().max(1.0) as u32
+    }
+}
+
+/// Keeps track of the prices at which items can currently be sold.
+#[derive(Resource, Default)]
+pub struct Market {
+    entries: HashMap<ItemId, MarketEntry>,
+}
+
+impl Market {
+    pub fn current_price(&self, item_id: &ItemId) -> Option<u32> {
+        self.entries.get(item_id).map(|entry| entry.current_price)
+    }
+
+    /// Sold items flood the market and lower the price until demand recovers.
+    pub fn record_sale(&mut self, item_id: &ItemId, amount: u32, season: Season) {
+        if let Some(entry) = self.entries.get_mut(item_id) {
+            entry.supply += amount as f32;
+            entry.current_price = entry.calculate_price(season);
+        }
+    }
+}
+
+fn initialize_market(
+    mut market: ResMut<Market>,
+    all_items: Res<AllItems>,
+    simulation_date: Res<SimulationDate>,
+) {
+    for crop in all_items.crops.values() {
+        for item_id in [
+            ItemId::Crop { crop_id: crop.id },
+            ItemId::Seed { crop_id: crop.id },
+        ] {
+            let Some(base_price) = sell_price(&item_id, &all_items) else {
+                continue;
+            };
+
+            let mut entry = MarketEntry::new(base_price, crop.seasons.clone());
+            entry.current_price = entry.calculate_price(simulation_date.season());
+            entry.history = VecDeque::from([entry.current_price]);
+            market.entries.insert(item_id, entry);
+        }
+    }
+}
+
+fn update_market_prices(
+    mut day_started_events: EventReader<DayStartedEvent>,
+    mut market: ResMut<Market>,
+    simulation_date: Res<SimulationDate>,
+) {
+    day_started_events.clear();
+
+    let season = simulation_date.season();
+    let mut random = rand::thread_rng();
+    for entry in market.entries.values_mut() {
+        entry.drift = (entry.drift + random.gen_range(-DAILY_DRIFT_STEP..DAILY_DRIFT_STEP))
+            .clamp(-MAX_DRIFT, MAX_DRIFT);
+        entry.supply *= SUPPLY_RETENTION;
+        entry.current_price = entry.calculate_price(season);
+
+        entry.history.push_back(entry.current_price);
+        if entry.history.len() > PRICE_HISTORY_LENGTH {
+            entry.history.pop_front();
+        }
+    }
+}
+
+const GRAPH_SIZE: egui::Vec2 = egui::Vec2::new(200.0, 50.0);
+
+fn market_ui(mut contexts: EguiContexts, market: Res<Market>, all_items: Res<AllItems>) {
+    let mut entries: Vec<_> = market.entries.iter().collect();
+    entries.sort_by_key(|(item_id, _)| item_id.item_name(&all_items));
+
+    egui::Window::new("Market")
+        .collapsible(true)
+        .default_open(false)
+        .resizable(false)
+        .anchor(Align2::LEFT_CENTER, egui::Vec2::new(0.0, 0.0))
+        .fixed_pos(Pos2::new(5.0, 0.0))
+        .show(contexts.ctx_mut(), |ui| {
+            for (item_id, entry) in entries {
+                ui.label(format!(
+                    "{}: {} Gold",
+                    item_id.item_name(&all_items),
+                    entry.current_price
+                ));
+                draw_price_history(ui, &entry.history);
+            }
+        });
+}
+
+fn draw_price_history(ui: &mut egui::Ui, history: &VecDeque<u32>) {
+    let (response, painter) = ui.allocate_painter(GRAPH_SIZE, egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(60));
+
+    if history.len() < 2 {
+        return;
+    }
+
+    let max = *history.iter().max().unwrap() as f32;
+    let min = *history.iter().min().unwrap() as f32;
+    let range = (max - min).max(1.0);
+    let step = rect.width() / (PRICE_HISTORY_LENGTH - 1) as f32;
+
+    let points: Vec<Pos2> = history
+        .iter()
+        .enumerate()
+        .map(|(i, price)| {
+            Pos2::new(
+                rect.left() + i as f32 * step,
+                rect.bottom() - (*price as f32 - min) / range * rect.height(),
+            )
+        })
+        .collect();
+
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.5, egui::Color32::LIGHT_GREEN),
+    ));
+}
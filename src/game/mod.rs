@@ -5,36 +5,56 @@ use leafwing_input_manager::plugin::InputManagerPlugin;
 
 use player::PlayerAction;
 
+use crate::game::blueprints::BlueprintPlugin;
 use crate::game::camera::CameraPlugin;
+use crate::game::chunk_simulation::ChunkSimulationPlugin;
+use crate::game::construction::ConstructionPlugin;
 use crate::game::drops::ItemPickupPlugin;
+use crate::game::economy::EconomyPlugin;
 use crate::game::interaction::InteractionPlugin;
 use crate::game::interaction_preview::InteractionPreviewPlugin;
 use crate::game::light::LightPlugin;
-use crate::game::tile_updater::TileUpdaterPlugin;
+use crate::game::market::MarketPlugin;
+use crate::game::roofs::RoofPlugin;
+use crate::game::rooms::RoomPlugin;
 use crate::game::tilemap::GameMapPlugin;
 use crate::game::ui::UiPlugin;
+use crate::game::undo::UndoPlugin;
 use crate::game::walls::WallPlugin;
+use crate::game::weather::WeatherPlugin;
 use crate::game::world_data::WorldDataPlugin;
 use crate::prelude::debug_actions::DebugActionPlugin;
 use crate::prelude::simulation_time::SimulationTimePlugin;
 use crate::prelude::PlayerPlugin;
 
 pub mod active_tool;
+pub mod blueprints;
 pub mod camera;
+pub mod chunk_simulation;
+pub mod collision;
+mod construction;
+mod crops;
 pub mod debug_actions;
 pub mod debug_overlay;
 mod drops;
+pub mod economy;
 pub mod interaction;
 mod interaction_preview;
 pub(crate) mod inventory;
 mod light;
 pub mod map_pos;
+pub mod market;
 pub mod player;
+mod props;
+mod roofs;
+pub mod rooms;
 pub mod simulation_time;
-pub mod tile_updater;
+pub mod spatial_index;
 pub mod tilemap;
 pub mod ui;
+mod undo;
 mod walls;
+pub mod weather;
 pub mod world_data;
 
 pub const CHUNK_SIZE: usize = 32;
@@ -57,9 +77,17 @@ impl Plugin for GamePlugin {
             .add_plugins(InteractionPlugin)
             .add_plugins(InteractionPreviewPlugin)
             .add_plugins(UiPlugin)
-            .add_plugins(TileUpdaterPlugin)
+            .add_plugins(ChunkSimulationPlugin)
             .add_plugins(DebugActionPlugin)
             .add_plugins(WallPlugin)
+            .add_plugins(ConstructionPlugin)
+            .add_plugins(UndoPlugin)
+            .add_plugins(BlueprintPlugin)
+            .add_plugins(RoomPlugin)
+            .add_plugins(RoofPlugin)
+            .add_plugins(WeatherPlugin)
+            .add_plugins(EconomyPlugin)
+            .add_plugins(MarketPlugin)
             .add_systems(First, update_cursor_pos);
     }
 }
@@ -83,7 +111,6 @@ pub fn update_cursor_pos(
     mut cursor_moved_events: EventReader<CursorMoved>,
     mut cursor_pos: ResMut<CursorPos>,
 ) {
-    // TODO: If we are using the gamepad, cursorpos should be playerPos + stick * range
     for cursor_moved in cursor_moved_events.read() {
         cursor_pos.screen = cursor_moved.position;
         for (cam_t, cam) in camera_q.iter() {
@@ -1,9 +1,9 @@
 use bevy::app::{App, Plugin, Update};
 use bevy::core::Name;
-use bevy::math::Vec3;
+use bevy::math::{Quat, Vec3};
 use bevy::prelude::{
-    default, in_state, Commands, Component, GamepadButtonType, IntoSystemConfigs, KeyCode,
-    MouseButton, OnEnter, Query, Reflect, Res, Time, Transform, With,
+    default, in_state, not, Commands, Component, GamepadButtonType, IntoSystemConfigs, KeyCode,
+    MouseButton, OnEnter, Query, Reflect, Res, Time, Transform, With, Without,
 };
 use bevy_sprite3d::{Sprite3d, Sprite3dParams};
 use leafwing_input_manager::action_state::ActionState;
@@ -13,24 +13,44 @@ use leafwing_input_manager::prelude::UserInput;
 use leafwing_input_manager::user_input::InputKind;
 use leafwing_input_manager::{Actionlike, InputManagerBundle};
 
+use data::prelude::{AllItems, CropId, ItemId};
+
+use crate::game::collision::{move_and_slide, Collider};
 use crate::game::drops::ItemMagnet;
+use crate::game::economy::{Wallet, STARTING_GOLD};
+use crate::game::interaction::Reach;
 use crate::load::SpriteAssets;
-use crate::prelude::camera::CameraFocus;
-use crate::prelude::{Inventory, SPRITE_DEFAULT_PIVOT, SPRITE_PIXELS_PER_METER};
+use crate::prelude::camera::{camera_yaw, CameraFocus, CameraMode, MainCameraParent};
+use crate::prelude::{
+    CardinalDirection, Inventory, WorldData, SPRITE_DEFAULT_PIVOT, SPRITE_PIXELS_PER_METER,
+};
 use crate::GameState;
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Playing), initialize_player);
-        app.add_systems(Update, move_player.run_if(in_state(GameState::Playing)));
+        app.add_systems(
+            Update,
+            move_player
+                .run_if(in_state(GameState::Playing))
+                .run_if(not(in_state(CameraMode::Free))),
+        );
     }
 }
 
 #[derive(Component)]
 pub struct ControlledByPlayer {}
 
+/// The direction an entity last moved towards.
+#[derive(Component)]
+pub struct FacingDirection {
+    pub direction: CardinalDirection,
+}
+
 const PLAYER_SPEED: f32 = 3.0;
+const PLAYER_REACH: f32 = 2.5;
+const PLAYER_COLLIDER_RADIUS: f32 = 0.25;
 
 fn initialize_player(
     mut commands: Commands,
@@ -48,28 +68,58 @@ fn initialize_player(
         }
         .bundle(&mut sprite_params),
         ControlledByPlayer {},
+        FacingDirection {
+            direction: CardinalDirection::South,
+        },
         InputManagerBundle::<PlayerAction> {
             input_map: default_input_map(),
             ..default()
         },
         CameraFocus {},
         ItemMagnet::default(),
-        Inventory::default(),
+        Collider {
+            radius: PLAYER_COLLIDER_RADIUS,
+        },
+        Reach {
+            distance: PLAYER_REACH,
+        },
+        starting_inventory(),
+        Wallet {
+            gold: STARTING_GOLD,
+        },
     ));
 }
 
+fn starting_inventory() -> Inventory {
+    let mut inventory = Inventory::default();
+    inventory.add_item(&ItemId::Seed { crop_id: CropId(0) }, 5);
+    inventory.add_item(&ItemId::Seed { crop_id: CropId(1) }, 5);
+    inventory
+}
+
 fn move_player(
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &ActionState<PlayerAction>), With<ControlledByPlayer>>,
+    world_data: Res<WorldData>,
+    all_items: Res<AllItems>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut FacingDirection,
+            &ActionState<PlayerAction>,
+            Option<&Collider>,
+        ),
+        With<ControlledByPlayer>,
+    >,
+    camera: Query<&Transform, (With<MainCameraParent>, Without<ControlledByPlayer>)>,
 ) {
-    let (mut transform, action_state) = query.single_mut();
+    let (mut transform, mut facing, action_state, collider) = query.single_mut();
     let mut dir;
     if action_state.pressed(PlayerAction::Move) {
-        dir = action_state
+        let stick = action_state
             .clamped_axis_pair(PlayerAction::Move)
             .unwrap()
-            .xy()
-            .extend(0.0);
+            .xy();
+        dir = Vec3::new(stick.x, 0.0, -stick.y);
     } else {
         dir = Vec3::ZERO;
     }
@@ -87,6 +137,11 @@ fn move_player(
         dir.x -= 1.0;
     }
 
+    // Input is relative to the camera, so "up" always moves away from it
+    if let Ok(camera) = camera.get_single() {
+        dir = Quat::from_rotation_y(camera_yaw(camera)) * dir;
+    }
+
     let speed = PLAYER_SPEED;
     let delta = {
         if dir.length() > 1.0 {
@@ -100,18 +155,42 @@ fn move_player(
         }
     };
 
-    transform.translation += delta;
+    if let Some(collider) = collider {
+        transform.translation = move_and_slide(
+            &world_data,
+            &all_items,
+            collider,
+            transform.translation,
+            delta,
+        );
+    } else {
+        transform.translation += delta;
+    }
+
+    if let Some(direction) = CardinalDirection::from_vec3(delta) {
+        if facing.direction != direction {
+            facing.direction = direction;
+        }
+    }
 }
 
 #[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
 pub enum PlayerAction {
     Move,
+    Aim,
     Up,
     Down,
     Left,
     Right,
     Interact,
     Rotate,
+    DropItem,
+    Cancel,
+    NextTool,
+    PreviousTool,
+    Undo,
+    Redo,
+    Blueprint,
     Hotbar1,
     Hotbar2,
     Hotbar3,
@@ -136,13 +215,36 @@ pub fn default_input_map() -> InputMap<PlayerAction> {
         ))),
         PlayerAction::Move,
     );
+    input_map.insert(
+        UserInput::Single(InputKind::DualAxis(DualAxis::right_stick().with_deadzone(
+            DeadZoneShape::Ellipse {
+                radius_x: 0.2,
+                radius_y: 0.2,
+            },
+        ))),
+        PlayerAction::Aim,
+    );
     // input_map.insert(UserInput::VirtualDPad(VirtualDPad::wasd()), Action::Move);
     // input_map.insert(UserInput::VirtualDPad(VirtualDPad::arrow_keys()), Action::Move);
     // input_map.insert(UserInput::VirtualDPad(VirtualDPad::dpad()), Action::Move);
 
     input_map.insert(MouseButton::Left, PlayerAction::Interact);
+    input_map.insert(GamepadButtonType::South, PlayerAction::Interact);
 
     input_map.insert(KeyCode::R, PlayerAction::Rotate);
+    input_map.insert(GamepadButtonType::West, PlayerAction::Rotate);
+    input_map.insert(KeyCode::G, PlayerAction::DropItem);
+    input_map.insert(GamepadButtonType::North, PlayerAction::DropItem);
+    input_map.insert(MouseButton::Right, PlayerAction::Cancel);
+    input_map.insert(KeyCode::Escape, PlayerAction::Cancel);
+    input_map.insert(GamepadButtonType::East, PlayerAction::Cancel);
+
+    input_map.insert(GamepadButtonType::RightTrigger, PlayerAction::NextTool);
+    input_map.insert(GamepadButtonType::LeftTrigger, PlayerAction::PreviousTool);
+
+    input_map.insert_chord([KeyCode::ControlLeft, KeyCode::Z], PlayerAction::Undo);
+    input_map.insert_chord([KeyCode::ControlLeft, KeyCode::Y], PlayerAction::Redo);
+    input_map.insert(KeyCode::B, PlayerAction::Blueprint);
 
     input_map.insert(KeyCode::Key1, PlayerAction::Hotbar1);
     input_map.insert(KeyCode::Key2, PlayerAction::Hotbar2);
@@ -0,0 +1,28 @@
+use bevy::core::Name;
+use bevy::prelude::{default, BuildChildren, Commands, Entity, PbrBundle, Transform};
+
+use data::prelude::PropDefinition;
+
+use crate::prelude::MapPos;
+
+pub fn spawn_prop_entity(
+    commands: &mut Commands,
+    chunk_parent: Entity,
+    pos: &MapPos,
+    definition: &PropDefinition,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new(definition.name.clone()),
+            PbrBundle {
+                mesh: definition.mesh.clone(),
+                material: definition.material.clone(),
+                transform: Transform::from_translation(
+                    pos.pos_inside_chunk(definition.height * 0.5),
+                ),
+                ..default()
+            },
+        ))
+        .set_parent(chunk_parent)
+        .id()
+}
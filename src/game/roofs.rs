@@ -0,0 +1,112 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::core::Name;
+use bevy::prelude::{
+    default, in_state, on_event, resource_changed, BuildChildren, Commands, Component, Condition,
+    DespawnRecursiveExt, Entity, Handle, IntoSystemConfigs, PbrBundle, Query, Res,
+    StandardMaterial, Transform, With,
+};
+use bevy::utils::HashSet;
+
+use crate::game::camera::CameraFocus;
+use crate::game::rooms::{RoomId, Rooms};
+use crate::prelude::loaded_chunks::{ChunkLoadedEvent, LoadedChunks};
+use crate::prelude::{DebugMaterials, DebugMeshes, GameState, MapPos};
+
+/// The height at which roofs are placed, right on top of the walls.
+const ROOF_HEIGHT: f32 = 2.05;
+
+pub struct RoofPlugin;
+impl Plugin for RoofPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                // Roofs are despawned together with their chunk, so unloading needs no extra handling
+                update_roofs
+                    .run_if(resource_changed::<Rooms>.or_else(on_event::<ChunkLoadedEvent>())),
+                update_roof_visibility.after(update_roofs),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// A single roof tile covering part of a room.
+#[derive(Component)]
+struct Roof {
+    room: RoomId,
+    pos: MapPos,
+}
+
+/// Roof tiles are children of their chunk, so they only exist while the chunk is loaded.
+fn update_roofs(
+    mut commands: Commands,
+    rooms: Res<Rooms>,
+    loaded_chunks: Res<LoadedChunks>,
+    meshes: Res<DebugMeshes>,
+    materials: Res<DebugMaterials>,
+    roofs: Query<(Entity, &Roof)>,
+) {
+    let mut covered_tiles = HashSet::new();
+    for (entity, roof) in roofs.iter() {
+        if rooms.contains(&roof.room) {
+            covered_tiles.insert((roof.room, roof.pos));
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for (id, room) in rooms.iter() {
+        for tile in room.tiles.iter() {
+            if covered_tiles.contains(&(*id, *tile)) {
+                continue;
+            }
+            let Some(loaded_data) = loaded_chunks.chunks.get(&tile.chunk) else {
+                continue;
+            };
+
+            commands
+                .spawn((
+                    Name::new(format!("Roof {} > {}", tile.chunk, tile.tile)),
+                    PbrBundle {
+                        mesh: meshes.roof.clone(),
+                        material: materials.roof.clone(),
+                        transform: Transform::from_translation(tile.pos_inside_chunk(ROOF_HEIGHT)),
+                        ..default()
+                    },
+                    Roof {
+                        room: *id,
+                        pos: *tile,
+                    },
+                ))
+                .set_parent(loaded_data.chunk_parent);
+        }
+    }
+}
+
+/// Hides the roof of whichever room the camera focus is standing in.
+/// It only gets a transparent material instead of being made invisible, so it still casts its shadow.
+fn update_roof_visibility(
+    rooms: Res<Rooms>,
+    materials: Res<DebugMaterials>,
+    camera_focus: Query<&Transform, With<CameraFocus>>,
+    mut roofs: Query<(&Roof, &mut Handle<StandardMaterial>)>,
+) {
+    let current_room = camera_focus
+        .get_single()
+        .ok()
+        .and_then(|transform| rooms.room_at(&MapPos::from_world_pos(transform.translation)))
+        .map(|(id, _)| id);
+
+    for (roof, mut material) in roofs.iter_mut() {
+        let wanted = if Some(roof.room) == current_room {
+            &materials.roof_hidden
+        } else {
+            &materials.roof
+        };
+
+        if *material != *wanted {
+            *material = wanted.clone();
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::{
+    in_state, on_event, EventReader, IntoSystemConfigs, OnEnter, Res, ResMut, Resource,
+};
+use bevy::utils::{HashMap, HashSet};
+
+use data::prelude::AllItems;
+
+use crate::game::walls::WallChangedEvent;
+use crate::prelude::helpers::neighbour_in_direction;
+use crate::prelude::{CardinalDirection, GameState, MapPos, TilePos, WorldData, CHUNK_SIZE};
+
+/// Areas bigger than this are considered to be outdoors.
+const MAX_ROOM_SIZE: usize = 512;
+
+pub struct RoomPlugin;
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rooms>()
+            .add_systems(OnEnter(GameState::Playing), detect_all_rooms)
+            .add_systems(
+                Update,
+                update_rooms
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(on_event::<WallChangedEvent>()),
+            );
+    }
+}
+
+pub type RoomId = u32;
+
+/// An area which is fully enclosed by walls.
+pub struct Room {
+    pub tiles: HashSet<MapPos>,
+}
+
+#[derive(Resource, Default)]
+pub struct Rooms {
+    rooms: HashMap<RoomId, Room>,
+    tile_to_room: HashMap<MapPos, RoomId>,
+    next_id: RoomId,
+}
+
+impl Rooms {
+    pub fn room_at(&self, pos: &MapPos) -> Option<(RoomId, &Room)> {
+        let id = self.tile_to_room.get(pos)?;
+        self.rooms.get(id).map(|room| (*id, room))
+    }
+
+    pub fn contains(&self, id: &RoomId) -> bool {
+        self.rooms.contains_key(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&RoomId, &Room)> {
+        self.rooms.iter()
+    }
+
+    fn remove_room_at(&mut self, pos: &MapPos) {
+        let Some(id) = self.tile_to_room.get(pos).copied() else {
+            return;
+        };
+
+        if let Some(room) = self.rooms.remove(&id) {
+            for tile in room.tiles.iter() {
+                self.tile_to_room.remove(tile);
+            }
+        }
+    }
+
+    /// Flood fills from the given tile and registers the result as a room if it is enclosed.
+    fn detect_room_at(&mut self, world_data: &WorldData, all_items: &AllItems, pos: MapPos) {
+        if self.tile_to_room.contains_key(&pos) {
+            return;
+        }
+
+        let Some(tiles) = flood_fill(world_data, all_items, pos) else {
+            return;
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+        for tile in tiles.iter() {
+            self.tile_to_room.insert(*tile, id);
+        }
+        self.rooms.insert(id, Room { tiles });
+    }
+}
+
+/// Returns all tiles reachable from start without crossing a wall.
+/// Returns None if the area touches the edge of the world or is too big to be a room.
+fn flood_fill(
+    world_data: &WorldData,
+    all_items: &AllItems,
+    start: MapPos,
+) -> Option<HashSet<MapPos>> {
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    visited.insert(start);
+    queue.push_back(start);
+
+    while let Some(pos) = queue.pop_front() {
+        let chunk = world_data.chunks.get(&pos.chunk)?;
+        let walls = chunk.at_pos(&pos.tile).walls;
+
+        for direction in CardinalDirection::ALL {
+            if walls.encloses_rooms_at(direction, all_items) {
+                continue;
+            }
+
+            let (chunk_pos, tile_pos) = neighbour_in_direction(&pos.chunk, &pos.tile, direction);
+            let neighbour = MapPos::new(chunk_pos, tile_pos);
+            if visited.contains(&neighbour) {
+                continue;
+            }
+
+            let neighbour_chunk = world_data.chunks.get(&neighbour.chunk)?;
+            if neighbour_chunk
+                .at_pos(&neighbour.tile)
+                .walls
+                .encloses_rooms_at(direction.opposite(), all_items)
+            {
+                continue;
+            }
+
+            visited.insert(neighbour);
+            if visited.len() > MAX_ROOM_SIZE {
+                return None;
+            }
+            queue.push_back(neighbour);
+        }
+    }
+
+    Some(visited)
+}
+
+/// Both tiles touching the given edge.
+fn tiles_next_to_edge(pos: MapPos, edge: CardinalDirection) -> [MapPos; 2] {
+    let (chunk_pos, tile_pos) = neighbour_in_direction(&pos.chunk, &pos.tile, edge);
+    [pos, MapPos::new(chunk_pos, tile_pos)]
+}
+
+fn detect_all_rooms(
+    mut rooms: ResMut<Rooms>,
+    world_data: Res<WorldData>,
+    all_items: Res<AllItems>,
+) {
+    for (chunk_pos, chunk) in world_data.chunks.iter() {
+        for y in 0..CHUNK_SIZE as u32 {
+            for x in 0..CHUNK_SIZE as u32 {
+                let walls = chunk.at(x, y).walls;
+                for direction in CardinalDirection::ALL {
+                    if !walls.encloses_rooms_at(direction, &all_items) {
+                        continue;
+                    }
+
+                    let pos = MapPos::new(*chunk_pos, TilePos::new(x, y));
+                    for pos in tiles_next_to_edge(pos, direction) {
+                        rooms.detect_room_at(&world_data, &all_items, pos);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Only the rooms next to changed walls need to be recalculated.
+fn update_rooms(
+    mut rooms: ResMut<Rooms>,
+    world_data: Res<WorldData>,
+    all_items: Res<AllItems>,
+    mut events: EventReader<WallChangedEvent>,
+) {
+    let mut dirty_tiles = Vec::new();
+    for event in events.read() {
+        if event.is_door_toggle {
+            continue;
+        }
+
+        for pos in tiles_next_to_edge(event.pos, event.edge) {
+            rooms.remove_room_at(&pos);
+            dirty_tiles.push(pos);
+        }
+    }
+
+    for pos in dirty_tiles {
+        rooms.detect_room_at(&world_data, &all_items, pos);
+    }
+}
@@ -3,16 +3,21 @@ use std::time::Duration;
 use bevy::app::{App, Plugin};
 use bevy::ecs::reflect::ReflectResource;
 use bevy::prelude::{
-    in_state, First, IntoSystemConfigs, Reflect, Res, ResMut, Resource, States, Time,
+    in_state, Event, EventWriter, First, IntoSystemConfigs, Reflect, Res, ResMut, Resource, States,
+    Time,
 };
 use bevy_inspector_egui::prelude::{InspectorOptions, ReflectInspectorOptions};
 
+use data::prelude::Season;
+
 pub struct SimulationTimePlugin;
 impl Plugin for SimulationTimePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(SimulationTime::default())
             .register_type::<SimulationTime>()
+            .register_type::<SimulationTick>()
             .insert_resource(SimulationDate::default())
+            .add_event::<DayStartedEvent>()
             .add_state::<SimulationState>()
             .add_systems(First, update.run_if(in_state(SimulationState::Running)));
     }
@@ -25,13 +30,24 @@ enum SimulationState {
     Paused,
 }
 
+/// Sent once the simulation crosses midnight.
+#[derive(Event)]
+pub struct DayStartedEvent;
+
 fn update(
     mut simulation_time: ResMut<SimulationTime>,
     mut date: ResMut<SimulationDate>,
+    mut day_started_events: EventWriter<DayStartedEvent>,
     real_time: Res<Time>,
 ) {
     simulation_time.advance(real_time.delta());
-    *date = SimulationDate::from_time(&simulation_time);
+
+    let new_date = SimulationDate::from_time(&simulation_time);
+    if !new_date.is_same_day(&date) {
+        day_started_events.send(DayStartedEvent);
+    }
+
+    *date = new_date;
 }
 
 #[derive(Resource, Reflect, InspectorOptions)]
@@ -43,6 +59,31 @@ pub struct SimulationTime {
     scale: f32,
 }
 
+/// A point in simulation time, counted in whole milliseconds since the world was created.
+/// Unlike f32 seconds, this doesn't lose precision no matter how long the game keeps running.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Reflect)]
+pub struct SimulationTick(pub u64);
+
+impl SimulationTick {
+    pub fn from_duration(duration: Duration) -> Self {
+        SimulationTick(duration.as_millis() as u64)
+    }
+
+    pub fn after(&self, duration: Duration) -> Self {
+        SimulationTick(self.0 + duration.as_millis() as u64)
+    }
+
+    /// The number of days which have started since the world was created.
+    pub fn day(&self) -> u64 {
+        (self.0 / 1000 + START_OFFSET) / SECONDS_PER_DAY
+    }
+
+    /// The time which has passed since earlier, or zero if earlier lies in the future.
+    pub fn duration_since(&self, earlier: SimulationTick) -> Duration {
+        Duration::from_millis(self.0.saturating_sub(earlier.0))
+    }
+}
+
 #[derive(Resource)]
 pub struct SimulationDate {
     pub year: u32,
@@ -77,6 +118,19 @@ const SECONDS_PER_MONTH: u64 = SECONDS_PER_DAY * DAYS_PER_MONTH;
 const SECONDS_PER_YEAR: u64 = SECONDS_PER_MONTH * MONTHS_PER_YEAR;
 
 impl SimulationDate {
+    pub fn is_same_day(&self, other: &SimulationDate) -> bool {
+        self.day == other.day && self.month == other.month && self.year == other.year
+    }
+
+    pub fn season(&self) -> Season {
+        match self.month {
+            1 => Season::Spring,
+            2 => Season::Summer,
+            3 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
     fn from_time(time: &SimulationTime) -> Self {
         let mut remaining_seconds = time.elapsed.as_secs() + START_OFFSET;
         let year = remaining_seconds / SECONDS_PER_YEAR;
@@ -135,7 +189,7 @@ impl SimulationTime {
     }
 
     #[inline]
-    pub fn elapsed_seconds_f32(&self) -> f32 {
-        self.elapsed.as_secs_f32()
+    pub fn now(&self) -> SimulationTick {
+        SimulationTick::from_duration(self.elapsed)
     }
 }
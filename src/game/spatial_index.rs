@@ -0,0 +1,57 @@
+use bevy::math::Vec3;
+use bevy::prelude::Entity;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::prelude::{ChunkPos, MapPos};
+
+/// Buckets entities by the chunk they are in, so proximity queries only need to look at nearby chunks.
+#[derive(Default)]
+pub struct SpatialIndex {
+    buckets: HashMap<ChunkPos, HashSet<Entity>>,
+    entity_buckets: HashMap<Entity, ChunkPos>,
+}
+
+impl SpatialIndex {
+    pub fn update(&mut self, entity: Entity, position: Vec3) {
+        let bucket = MapPos::from_world_pos(position).chunk;
+        if let Some(previous) = self.entity_buckets.insert(entity, bucket) {
+            if previous == bucket {
+                return;
+            }
+
+            self.remove_from_bucket(entity, previous);
+        }
+
+        self.buckets.entry(bucket).or_default().insert(entity);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        if let Some(bucket) = self.entity_buckets.remove(&entity) {
+            self.remove_from_bucket(entity, bucket);
+        }
+    }
+
+    /// All entities inside buckets overlapping the square around `center`. Callers still need to check the exact distance.
+    pub fn query(&self, center: Vec3, radius: f32) -> impl Iterator<Item = Entity> + '_ {
+        let min = MapPos::from_world_pos(center - Vec3::new(radius, 0.0, radius)).chunk;
+        let max = MapPos::from_world_pos(center + Vec3::new(radius, 0.0, radius)).chunk;
+
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| ChunkPos::new(x, y)))
+            .filter_map(|bucket| self.buckets.get(&bucket))
+            .flat_map(|entities| entities.iter().copied())
+    }
+
+    pub fn buckets(&self) -> impl Iterator<Item = (&ChunkPos, &HashSet<Entity>)> {
+        self.buckets.iter()
+    }
+
+    fn remove_from_bucket(&mut self, entity: Entity, bucket: ChunkPos) {
+        if let Some(entities) = self.buckets.get_mut(&bucket) {
+            entities.remove(&entity);
+            if entities.is_empty() {
+                self.buckets.remove(&bucket);
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::game::CHUNK_SIZE;
-use crate::prelude::{ChunkPos, TilePos, WorldData};
+use crate::prelude::{CardinalDirection, ChunkPos, TilePos, WorldData};
 
 pub fn left_of(chunk_pos: &ChunkPos, tile_pos: &TilePos) -> (ChunkPos, TilePos) {
     if tile_pos.x == 0 {
@@ -45,6 +45,19 @@ pub fn top_of(chunk_pos: &ChunkPos, tile_pos: &TilePos) -> (ChunkPos, TilePos) {
     }
 }
 
+pub fn neighbour_in_direction(
+    chunk_pos: &ChunkPos,
+    tile_pos: &TilePos,
+    direction: CardinalDirection,
+) -> (ChunkPos, TilePos) {
+    match direction {
+        CardinalDirection::North => below_of(chunk_pos, tile_pos),
+        CardinalDirection::East => right_of(chunk_pos, tile_pos),
+        CardinalDirection::South => top_of(chunk_pos, tile_pos),
+        CardinalDirection::West => left_of(chunk_pos, tile_pos),
+    }
+}
+
 // 00 01 02 03
 // 04 05 06 07
 // 08 09 10 11
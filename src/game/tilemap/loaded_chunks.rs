@@ -1,7 +1,8 @@
-use bevy::prelude::{App, Entity, Plugin, Resource};
+use bevy::prelude::{App, Entity, Event, Plugin, Resource};
 use bevy::utils::HashMap;
 
 use crate::game::CHUNK_SIZE;
+use crate::prelude::chunk_data::DropId;
 use crate::prelude::{ChunkPos, TilePos};
 
 pub struct LoadedChunkPlugin;
@@ -9,10 +10,20 @@ impl Plugin for LoadedChunkPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(LoadedChunks {
             chunks: HashMap::new(),
-        });
+        })
+        .add_event::<ChunkLoadedEvent>()
+        .add_event::<ChunkUnloadedEvent>();
     }
 }
 
+/// Sent once a chunk has been spawned around the player.
+#[derive(Event)]
+pub struct ChunkLoadedEvent(pub ChunkPos);
+
+/// Sent once a chunk has been despawned, together with everything inside it.
+#[derive(Event)]
+pub struct ChunkUnloadedEvent(pub ChunkPos);
+
 #[derive(Resource)]
 pub struct LoadedChunks {
     pub chunks: HashMap<ChunkPos, LoadedChunkData>,
@@ -22,6 +33,8 @@ pub struct LoadedChunkData {
     pub chunk_parent: Entity,
     pub tiles: [Option<Entity>; CHUNK_SIZE * CHUNK_SIZE],
     pub crops: HashMap<TilePos, Entity>,
+    pub props: HashMap<TilePos, Entity>,
+    pub drops: HashMap<DropId, Entity>,
 }
 
 impl LoadedChunkData {
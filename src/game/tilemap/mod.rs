@@ -6,18 +6,31 @@ use bevy::prelude::*;
 use bevy::utils::hashbrown::HashMap;
 use bevy_mod_raycast::deferred::DeferredRaycastingPlugin;
 use bevy_mod_raycast::prelude::RaycastMesh;
+use bevy_sprite3d::AtlasSprite3dComponent;
 
-use crate::game::tilemap::loaded_chunks::{LoadedChunkPlugin, LoadedChunks};
-use crate::game::tilemap::update_tile_event::UpdateTileEventPlugin;
+use crate::game::chunk_simulation::{show_simulation_changes, simulate_chunks};
+use crate::game::crops::spawn_crop_entity;
+use crate::game::drops::{spawn_item_drop_entity, DropSpawnParams};
+use crate::game::player::ControlledByPlayer;
+use crate::game::props::spawn_prop_entity;
+use crate::game::tilemap::loaded_chunks::{
+    ChunkLoadedEvent, ChunkUnloadedEvent, LoadedChunkPlugin, LoadedChunks,
+};
+use crate::game::tilemap::update_tile_event::{
+    tile_material, UpdateTileEvent, UpdateTileEventPlugin,
+};
+use crate::game::walls::{build_and_spawn_door_entity, build_and_spawn_wall_entity};
 use crate::prelude::chunk_identifier::ChunkIdentifier;
 use crate::prelude::loaded_chunks::LoadedChunkData;
 use crate::prelude::tile_cursor::TileCursorPlugin;
+use crate::prelude::tile_data::EdgeStructure;
 use crate::prelude::tile_grid_gizmo::TileGridGizmo;
-use crate::prelude::{
-    ChunkPos, DebugMaterials, DebugMeshes, WorldData, CHUNK_SIZE, DEBUG_WORLD_SIZE_MIN_AND_MAX,
-};
+use crate::prelude::{CardinalDirection, ChunkPos, MapPos, SimulationTime, WorldData, CHUNK_SIZE};
 use crate::GameState;
 
+/// Chunks further away from the player than this are unloaded and stop being simulated.
+const CHUNK_LOAD_RADIUS: i32 = 1;
+
 pub(crate) mod chunk_identifier;
 pub(crate) mod helpers;
 pub(crate) mod loaded_chunks;
@@ -36,43 +49,101 @@ impl Plugin for GameMapPlugin {
             .add_plugins(TileGridGizmo)
             .add_plugins(UpdateTileEventPlugin)
             .add_plugins(LoadedChunkPlugin)
-            .add_systems(OnEnter(GameState::Playing), spawn_testing_chunks);
+            .add_systems(
+                Update,
+                update_loaded_chunks.run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
-fn spawn_testing_chunks(
+/// Loads all chunks around the player and unloads the ones which are too far away.
+/// Chunks are caught up on everything they missed while unloaded right before they are spawned.
+fn update_loaded_chunks(
     mut commands: Commands,
-    world_data: Res<WorldData>,
+    mut world_data: ResMut<WorldData>,
     mut loaded_chunks: ResMut<LoadedChunks>,
-    meshes: Res<DebugMeshes>,
-    materials: Res<DebugMaterials>,
+    mut drop_params: DropSpawnParams,
+    simulation_time: Res<SimulationTime>,
+    player: Query<&Transform, With<ControlledByPlayer>>,
+    mut update_tile_events: EventWriter<UpdateTileEvent>,
+    mut chunk_loaded_events: EventWriter<ChunkLoadedEvent>,
+    mut chunk_unloaded_events: EventWriter<ChunkUnloadedEvent>,
+    mut sprites: Query<&mut AtlasSprite3dComponent>,
+    mut previous_center: Local<Option<ChunkPos>>,
 ) {
-    // FIXME: These should be created in the loading process
-    // FIXME: Materials should use the texture from the spritesheet.
-    let tile_mesh = meshes.tile.clone();
-    let tile_material = materials.grass.clone();
-
-    for x in -DEBUG_WORLD_SIZE_MIN_AND_MAX..DEBUG_WORLD_SIZE_MIN_AND_MAX {
-        for y in -DEBUG_WORLD_SIZE_MIN_AND_MAX..DEBUG_WORLD_SIZE_MIN_AND_MAX {
-            spawn_chunk(
-                &mut commands,
-                ChunkPos::new(x, y),
-                &world_data,
-                &mut loaded_chunks,
-                &tile_mesh,
-                &tile_material,
-            );
+    let Ok(transform) = player.get_single() else {
+        return;
+    };
+    let center = MapPos::from_world_pos(transform.translation).chunk;
+    if *previous_center == Some(center) {
+        return;
+    }
+    *previous_center = Some(center);
+
+    let is_in_range =
+        |chunk_pos: &ChunkPos| (*chunk_pos - center).abs().max_element() <= CHUNK_LOAD_RADIUS;
+
+    // Loaded chunks are simulated every frame, so they are already up to date when being unloaded
+    let out_of_range: Vec<ChunkPos> = loaded_chunks
+        .chunks
+        .keys()
+        .filter(|chunk_pos| !is_in_range(chunk_pos))
+        .copied()
+        .collect();
+    for chunk_pos in out_of_range {
+        despawn_chunk(&mut commands, &mut loaded_chunks, chunk_pos);
+        chunk_unloaded_events.send(ChunkUnloadedEvent(chunk_pos));
+    }
+
+    let mut chunks_to_load = Vec::new();
+    for y in -CHUNK_LOAD_RADIUS..=CHUNK_LOAD_RADIUS {
+        for x in -CHUNK_LOAD_RADIUS..=CHUNK_LOAD_RADIUS {
+            let chunk_pos = center + ChunkPos::new(x, y);
+            if !loaded_chunks.chunks.contains_key(&chunk_pos)
+                && world_data.chunks.contains_key(&chunk_pos)
+            {
+                chunks_to_load.push(chunk_pos);
+            }
         }
     }
+
+    // Weeds might spread into chunks which are already loaded
+    let changes = simulate_chunks(
+        &mut world_data,
+        |chunk_pos| chunks_to_load.contains(chunk_pos),
+        simulation_time.now(),
+        &drop_params.all_items,
+    );
+    show_simulation_changes(
+        &changes,
+        &world_data,
+        &loaded_chunks,
+        &mut update_tile_events,
+        &mut sprites,
+    );
+
+    for chunk_pos in chunks_to_load {
+        spawn_chunk(
+            &mut commands,
+            chunk_pos,
+            &world_data,
+            &mut loaded_chunks,
+            &mut drop_params,
+        );
+        chunk_loaded_events.send(ChunkLoadedEvent(chunk_pos));
+    }
 }
 
 fn get_chunk_name(chunk_pos: ChunkPos) -> Name {
     Name::new(format!("Chunk {}", chunk_pos))
 }
 
-fn despawn_chunk(mut commands: Commands, loaded_chunks: &mut LoadedChunks, chunk_pos: ChunkPos) {
+fn despawn_chunk(commands: &mut Commands, loaded_chunks: &mut LoadedChunks, chunk_pos: ChunkPos) {
     if let Some(chunk) = loaded_chunks.chunks.remove(&chunk_pos) {
         commands.entity(chunk.chunk_parent).despawn_recursive();
+        for entity in chunk.drops.values() {
+            commands.entity(*entity).despawn();
+        }
     }
 }
 
@@ -99,8 +170,7 @@ fn spawn_chunk(
     chunk_pos: ChunkPos,
     world_data: &WorldData,
     loaded_chunks: &mut LoadedChunks,
-    tile_mesh: &Handle<Mesh>,
-    tile_material: &Handle<StandardMaterial>,
+    drop_params: &mut DropSpawnParams,
 ) {
     let chunk_data = world_data
         .chunks
@@ -122,13 +192,17 @@ fn spawn_chunk(
         ))
         .id();
 
+    // FIXME: Materials should use the texture from the spritesheet.
     for x in 0..CHUNK_SIZE {
         for z in 0..CHUNK_SIZE {
             let entity = commands
                 .spawn((
                     PbrBundle {
-                        mesh: tile_mesh.clone(),
-                        material: tile_material.clone(),
+                        mesh: drop_params.meshes.tile.clone(),
+                        material: tile_material(
+                            chunk_data.at(x as u32, z as u32),
+                            &drop_params.materials,
+                        ),
                         transform: get_tile_transform(x as f32, z as f32),
                         ..default()
                     },
@@ -143,13 +217,84 @@ fn spawn_chunk(
                 .id();
 
             tiles[x + z * CHUNK_SIZE] = Some(entity);
+
+            let tile_pos = TilePos::new(x as u32, z as u32);
+            let pos = MapPos::new(chunk_pos, tile_pos);
+            for edge in CardinalDirection::ALL {
+                match chunk_data.at_pos(&tile_pos).walls.at(edge) {
+                    Some(EdgeStructure::Wall { structure_id }) => {
+                        let Some(definition) =
+                            drop_params.all_items.edge_structures.get(&structure_id)
+                        else {
+                            error!("Unable to find edge structure with id {}", structure_id.0);
+                            continue;
+                        };
+                        build_and_spawn_wall_entity(commands, entity, pos, edge, definition);
+                    }
+                    Some(EdgeStructure::Door { is_open }) => {
+                        build_and_spawn_door_entity(
+                            commands,
+                            entity,
+                            pos,
+                            edge,
+                            is_open,
+                            &drop_params.meshes,
+                            &drop_params.materials,
+                        );
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    let mut crops = HashMap::new();
+    for (tile_pos, crop) in chunk_data.crops.iter() {
+        let Some(definition) = drop_params.all_items.crops.get(&crop.crop_id) else {
+            error!("Unable to find crop with id {}", crop.crop_id.0);
+            continue;
+        };
+
+        let entity = spawn_crop_entity(
+            commands,
+            &mut drop_params.sprite_params,
+            chunk_parent,
+            &MapPos::new(chunk_pos, tile_pos.clone()),
+            definition,
+            crop.stage,
+        );
+        crops.insert(tile_pos.clone(), entity);
+    }
+
+    let mut props = HashMap::new();
+    for (tile_pos, prop) in chunk_data.props.iter() {
+        let Some(definition) = drop_params.all_items.props.get(&prop.prop_id) else {
+            error!("Unable to find prop with id {}", prop.prop_id.0);
+            continue;
+        };
+
+        let entity = spawn_prop_entity(
+            commands,
+            chunk_parent,
+            &MapPos::new(chunk_pos, tile_pos.clone()),
+            definition,
+        );
+        props.insert(tile_pos.clone(), entity);
+    }
+
+    let mut drops = HashMap::new();
+    for (id, drop) in chunk_data.drops.iter() {
+        if let Some(entity) = spawn_item_drop_entity(commands, drop_params, chunk_pos, *id, drop) {
+            drops.insert(*id, entity);
         }
     }
 
     let loaded_chunk_data = LoadedChunkData {
         chunk_parent,
         tiles,
-        crops: HashMap::new(),
+        crops,
+        props,
+        drops,
     };
 
     loaded_chunks.chunks.insert(chunk_pos, loaded_chunk_data);
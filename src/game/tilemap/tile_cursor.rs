@@ -1,31 +1,50 @@
 use bevy::app::{App, First, Plugin};
 use bevy::core::Name;
+use bevy::input::gamepad::{GamepadAxisChangedEvent, GamepadButtonChangedEvent};
+use bevy::input::mouse::MouseButtonInput;
 use bevy::math::{IVec2, Quat};
 use bevy::pbr::NotShadowCaster;
 use bevy::prelude::*;
 use bevy_mod_raycast::prelude::{RaycastMesh, RaycastSource};
 use bevy_sprite3d::{Sprite3d, Sprite3dParams};
+use leafwing_input_manager::action_state::ActionState;
 
+use data::prelude::AreaPattern;
+
+use crate::game::camera::{camera_yaw, MainCameraParent};
+use crate::game::interaction::Reach;
+use crate::game::player::{ControlledByPlayer, FacingDirection, PlayerAction};
 use crate::prelude::chunk_identifier::ChunkIdentifier;
 use crate::prelude::{
-    CardinalDirection, MapPos, MouseCursorOverUiState, TilePos, CHUNK_SIZE, SPRITE_PIXELS_PER_METER,
+    ActiveTool, CardinalDirection, MapPos, MouseCursorOverUiState, TilePos, SPRITE_PIXELS_PER_METER,
 };
-use crate::prelude::{SpriteAssets, TileRaycastSet};
+use crate::prelude::{DebugMaterials, SpriteAssets, TileRaycastSet};
 use crate::GameState;
 
 pub struct TileCursorPlugin;
 impl Plugin for TileCursorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.add_state::<CursorInputState>().add_systems(
             First,
             (
+                detect_cursor_input_device.run_if(in_state(GameState::Playing)),
                 update_mouse_cursor
+                    .after(detect_cursor_input_device)
                     .run_if(in_state(GameState::Playing))
+                    .run_if(in_state(CursorInputState::Mouse))
                     .run_if(in_state(MouseCursorOverUiState::NotOverUI)),
+                update_gamepad_cursor
+                    .after(detect_cursor_input_device)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(in_state(CursorInputState::Gamepad)),
                 update_tile_cursor
                     .after(update_mouse_cursor)
+                    .after(update_gamepad_cursor)
                     .run_if(in_state(GameState::Playing))
-                    .run_if(in_state(MouseCursorOverUiState::NotOverUI)),
+                    .run_if(
+                        in_state(MouseCursorOverUiState::NotOverUI)
+                            .or_else(in_state(CursorInputState::Gamepad)),
+                    ),
             ),
         );
     }
@@ -34,6 +53,8 @@ impl Plugin for TileCursorPlugin {
 #[derive(Component, Debug)]
 pub struct TileCursor {
     pub pos: MapPos,
+    /// Whether the player is close enough to interact with this tile.
+    pub in_reach: bool,
 }
 
 /// An optional resource detailing which tile the mouse cursor is hovering over.
@@ -45,12 +66,27 @@ pub struct MouseCursorOnTile {
     pub tile_edge: CardinalDirection,
 }
 
+/// Which input device is used to pick tiles. Switches automatically to whichever device was used last.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum CursorInputState {
+    #[default]
+    Mouse,
+    Gamepad,
+}
+
+/// An optional resource which exists while the player is dragging out a rectangle of tiles
+/// or charging up a tool on a single one.
+#[derive(Resource, Debug)]
+pub struct AreaSelection {
+    pub start: MapPos,
+    pub charge_time: f32,
+}
+
+const CHARGE_SECONDS_PER_LEVEL: f32 = 0.6;
+
 impl TileCursor {
     pub fn global_position(&self) -> IVec2 {
-        IVec2::new(
-            self.pos.chunk.x * CHUNK_SIZE as i32 + self.pos.tile.x as i32,
-            self.pos.chunk.y * CHUNK_SIZE as i32 + self.pos.tile.y as i32,
-        )
+        self.pos.global_tile_pos()
     }
 }
 
@@ -148,22 +184,190 @@ fn update_mouse_cursor(
     commands.remove_resource::<MouseCursorOnTile>()
 }
 
+fn detect_cursor_input_device(
+    current_state: Res<State<CursorInputState>>,
+    mut next_state: ResMut<NextState<CursorInputState>>,
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    mut mouse_button_events: EventReader<MouseButtonInput>,
+    mut gamepad_button_events: EventReader<GamepadButtonChangedEvent>,
+    mut gamepad_axis_events: EventReader<GamepadAxisChangedEvent>,
+) {
+    let used_mouse =
+        cursor_moved_events.read().count() > 0 || mouse_button_events.read().count() > 0;
+    let used_gamepad = gamepad_button_events.read().count() > 0
+        || gamepad_axis_events
+            .read()
+            .any(|event| event.value.abs() > GAMEPAD_AXIS_THRESHOLD);
+
+    let new_state = if used_gamepad {
+        CursorInputState::Gamepad
+    } else if used_mouse {
+        CursorInputState::Mouse
+    } else {
+        return;
+    };
+
+    if current_state.get() != &new_state {
+        next_state.set(new_state);
+    }
+}
+
+/// Axis changes below this value are treated as stick drift and won't switch to gamepad input.
+const GAMEPAD_AXIS_THRESHOLD: f32 = 0.3;
+
+/// Places the cursor next to the player, either where the right stick is pointing at or in front of them.
+fn update_gamepad_cursor(
+    mut commands: Commands,
+    player: Query<
+        (
+            &Transform,
+            &FacingDirection,
+            &ActionState<PlayerAction>,
+            &Reach,
+        ),
+        With<ControlledByPlayer>,
+    >,
+    camera: Query<&Transform, (With<MainCameraParent>, Without<ControlledByPlayer>)>,
+) {
+    let Ok((transform, facing, action_state, reach)) = player.get_single() else {
+        return;
+    };
+
+    let offset = match action_state.clamped_axis_pair(&PlayerAction::Aim) {
+        Some(axis) if axis.xy() != Vec2::ZERO => {
+            let yaw = camera.get_single().map_or(0.0, camera_yaw);
+            Quat::from_rotation_y(yaw) * Vec3::new(axis.x(), 0.0, -axis.y()) * reach.distance
+        }
+        _ => facing.direction.as_vec3(),
+    };
+
+    let cursor_pos = transform.translation + offset;
+    commands.insert_resource(MouseCursorOnTile {
+        tile_pos: MapPos::from_world_pos(cursor_pos),
+        sub_tile: IVec2::ZERO,
+        mouse_pos: cursor_pos,
+        tile_edge: intersection_to_tile_edge(cursor_pos),
+    });
+}
+
+/// Returns all tiles in the rectangle spanned by start and end, shrunk towards start to fit max_size.
+fn area_tiles(start: MapPos, end: MapPos, max_size: u32) -> Vec<MapPos> {
+    let start = start.global_tile_pos();
+    let end = end.global_tile_pos();
+    let max_offset = max_size as i32 - 1;
+    let end = start + (end - start).clamp(IVec2::splat(-max_offset), IVec2::splat(max_offset));
+
+    let min = start.min(end);
+    let max = start.max(end);
+
+    let mut result = Vec::new();
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            result.push(MapPos::from_global_tile_pos(IVec2::new(x, y)));
+        }
+    }
+
+    result
+}
+
+/// Returns all tiles affected by the given pattern when targeting origin.
+fn pattern_tiles(origin: MapPos, pattern: AreaPattern, facing: CardinalDirection) -> Vec<MapPos> {
+    let origin = origin.global_tile_pos();
+    let offsets: Vec<IVec2> = match pattern {
+        AreaPattern::Single => vec![IVec2::ZERO],
+        AreaPattern::Line3 => {
+            let direction = facing.as_vec3();
+            let direction = IVec2::new(direction.x as i32, direction.z as i32);
+            vec![IVec2::ZERO, direction, direction * 2]
+        }
+        AreaPattern::Square3 => {
+            let mut offsets = Vec::new();
+            for y in -1..=1 {
+                for x in -1..=1 {
+                    offsets.push(IVec2::new(x, y));
+                }
+            }
+            offsets
+        }
+    };
+
+    offsets
+        .into_iter()
+        .map(|offset| MapPos::from_global_tile_pos(origin + offset))
+        .collect()
+}
+
 fn update_tile_cursor(
     mut commands: Commands,
     mut sprite_params: Sprite3dParams,
     assets: Res<SpriteAssets>,
+    time: Res<Time>,
+    active_tool: Res<ActiveTool>,
+    debug_materials: Res<DebugMaterials>,
+    player: Query<(
+        &ActionState<PlayerAction>,
+        &FacingDirection,
+        &Transform,
+        &Reach,
+    )>,
     mouse_cursor: Option<Res<MouseCursorOnTile>>,
+    area_selection: Option<ResMut<AreaSelection>>,
     tile_cursor_q: Query<(Entity, &TileCursor)>,
 ) {
     let Some(mouse_cursor) = mouse_cursor else {
         return;
     };
 
-    let this_frame_selection: Vec<MapPos> = vec![mouse_cursor.tile_pos];
+    let max_size = active_tool.max_area_selection_size();
+    let this_frame_selection: Vec<MapPos> = match player.get_single() {
+        Ok((action_state, facing, _, _))
+            if active_tool.uses_area_selection()
+                && action_state.pressed(&PlayerAction::Interact)
+                && !action_state.just_pressed(&PlayerAction::Cancel) =>
+        {
+            if let Some(mut area_selection) = area_selection {
+                if area_selection.start == mouse_cursor.tile_pos {
+                    area_selection.charge_time += time.delta_seconds();
+                    let charge_level = ((area_selection.charge_time / CHARGE_SECONDS_PER_LEVEL)
+                        as u32)
+                        .min(active_tool.max_charge_level());
+                    pattern_tiles(
+                        area_selection.start,
+                        AreaPattern::from_charge_level(charge_level),
+                        facing.direction,
+                    )
+                } else {
+                    area_selection.charge_time = 0.0;
+                    area_tiles(area_selection.start, mouse_cursor.tile_pos, max_size)
+                }
+            } else {
+                if action_state.just_pressed(&PlayerAction::Interact) {
+                    commands.insert_resource(AreaSelection {
+                        start: mouse_cursor.tile_pos,
+                        charge_time: 0.0,
+                    });
+                }
+                vec![mouse_cursor.tile_pos]
+            }
+        }
+        _ => {
+            if area_selection.is_some() {
+                commands.remove_resource::<AreaSelection>();
+            }
+            vec![mouse_cursor.tile_pos]
+        }
+    };
+
+    let is_in_reach = |pos: &MapPos| match player.get_single() {
+        Ok((_, _, transform, reach)) => reach.contains(transform.translation, pos),
+        Err(_) => false,
+    };
 
     let mut already_existing_cursors: Vec<MapPos> = Vec::new();
     for (entity, cursor) in tile_cursor_q.iter() {
-        if this_frame_selection.iter().any(|pos| pos == &cursor.pos) {
+        if this_frame_selection.iter().any(|pos| pos == &cursor.pos)
+            && cursor.in_reach == is_in_reach(&cursor.pos)
+        {
             already_existing_cursors.push(cursor.pos);
         } else {
             commands.entity(entity).despawn();
@@ -172,7 +376,8 @@ fn update_tile_cursor(
 
     for selected_tile in this_frame_selection.iter() {
         if !already_existing_cursors.contains(selected_tile) {
-            commands.spawn((
+            let in_reach = is_in_reach(selected_tile);
+            let mut entity = commands.spawn((
                 Name::new(format!(
                     "Tile Cursor {} > {}",
                     selected_tile.chunk, selected_tile.tile
@@ -191,9 +396,14 @@ fn update_tile_cursor(
                 .bundle(&mut sprite_params),
                 TileCursor {
                     pos: selected_tile.clone(),
+                    in_reach,
                 },
                 NotShadowCaster,
             ));
+
+            if !in_reach {
+                entity.insert(debug_materials.out_of_reach_cursor.clone());
+            }
         }
     }
 }
@@ -6,6 +6,7 @@ use bevy::prelude::{
 use crate::game::tilemap::helpers::{below_of, left_of, right_of, top_of};
 use crate::load::DebugMaterials;
 use crate::prelude::loaded_chunks::LoadedChunks;
+use crate::prelude::tile_data::TileData;
 use crate::prelude::{ChunkPos, WorldData};
 use crate::prelude::{GameState, TilePos};
 
@@ -60,15 +61,23 @@ fn update_tiles(
                     .get_tile(event.tile_pos.x, event.tile_pos.y)
                     .unwrap();
                 let mut material = tiles.get_mut(tile_entity).unwrap();
-
-                if tile.is_tilled {
-                    // FIXME: determine which texture we wanna use, maybe use a TextureAtlas while we are at it
-                    // determine_texture_index(&event.tile_pos, &event.chunk_pos, &world_data);
-                    *material = debug_materials.tilled.clone();
-                } else {
-                    *material = debug_materials.grass.clone();
-                }
+                *material = tile_material(tile, &debug_materials);
             }
         }
     }
 }
+
+pub fn tile_material(
+    tile: &TileData,
+    debug_materials: &DebugMaterials,
+) -> Handle<StandardMaterial> {
+    if tile.has_weeds {
+        debug_materials.weeds.clone()
+    } else if tile.is_tilled {
+        // FIXME: determine which texture we wanna use, maybe use a TextureAtlas while we are at it
+        // determine_texture_index(&event.tile_pos, &event.chunk_pos, &world_data);
+        debug_materials.tilled.clone()
+    } else {
+        debug_materials.grass.clone()
+    }
+}
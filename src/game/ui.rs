@@ -8,6 +8,8 @@ use bevy_egui::{egui, EguiContexts, EguiPlugin};
 
 use data::prelude::AllItems;
 
+use crate::game::rooms::Rooms;
+use crate::game::weather::Weather;
 use crate::prelude::chunk_data::ChunkData;
 use crate::prelude::tile_cursor::TileCursor;
 use crate::prelude::{
@@ -54,10 +56,12 @@ fn ui_system(
     mut contexts: EguiContexts,
     cursor: Query<&TileCursor>,
     world_data: Res<WorldData>,
-    active_tool: Res<ActiveTool>,
+    mut active_tool: ResMut<ActiveTool>,
     simulation_time: Res<SimulationTime>,
     simulation_date: Res<SimulationDate>,
     all_items: Res<AllItems>,
+    rooms: Res<Rooms>,
+    weather: Res<Weather>,
     inventories: Query<(&Name, &Inventory)>,
 ) {
     if let Ok(cursor) = cursor.get_single() {
@@ -78,6 +82,9 @@ fn ui_system(
                     &simulation_time,
                     &all_items,
                 ));
+                if let Some((id, room)) = rooms.room_at(&cursor.pos) {
+                    ui.label(format!("Room #{} ({} tiles)", id, room.tiles.len()));
+                }
             });
     }
 
@@ -97,12 +104,14 @@ fn ui_system(
         .fixed_pos(Pos2::new(0.0, 5.0))
         .show(contexts.ctx_mut(), |ui| {
             ui.label(format!(
-                "{:0>2}.{:0>2} (Year {}) | {:0>2}:{:0>2}",
+                "{:0>2}.{:0>2} (Year {}, {}) | {:0>2}:{:0>2}{}",
                 simulation_date.day,
                 simulation_date.month,
                 simulation_date.year,
+                simulation_date.season(),
                 simulation_date.hour,
                 simulation_date.minute,
+                if weather.is_raining { " | Rain" } else { "" },
             ))
         });
 
@@ -120,7 +129,16 @@ fn ui_system(
                             content.label("Empty!");
                         } else {
                             for (id, amount) in inventory.into_iter() {
-                                content.label(format!("{}: {}", id.item_name(&all_items), amount));
+                                let is_selected = *active_tool == ActiveTool::Item(*id);
+                                if content
+                                    .selectable_label(
+                                        is_selected,
+                                        format!("{}: {}", id.item_name(&all_items), amount),
+                                    )
+                                    .clicked()
+                                {
+                                    *active_tool = ActiveTool::Item(*id);
+                                }
                             }
                         }
                     });
@@ -142,10 +160,20 @@ fn map_data_for_position(
     let tile = chunk.at_pos(&pos.tile);
 
     lines.push(format!(
-        "Tile: {:?}\n  is_tilled: {}",
-        tile.ground_type, tile.is_tilled
+        "Tile: {:?}\n  is_tilled: {}\n  is_watered: {}\n  has_weeds: {}",
+        tile.ground_type, tile.is_tilled, tile.is_watered, tile.has_weeds
     ));
 
+    if let Some(prop) = chunk.props.get(&pos.tile) {
+        let definition = all_items.props.get(&prop.prop_id).unwrap();
+        lines.push(format!("Prop: {} ({})", definition.name, prop.prop_id.0));
+        if let Some(inventory) = &prop.inventory {
+            for (id, amount) in inventory {
+                lines.push(format!("  {}: {}", id.item_name(all_items), amount));
+            }
+        }
+    }
+
     if let Some(crop) = chunk.crops.get(&pos.tile) {
         let definition = all_items.crops.get(&crop.crop_id).unwrap();
         lines.push(format!("Crop: {} ({})", definition.name, crop.crop_id.0));
@@ -153,7 +181,9 @@ fn map_data_for_position(
         if let Some(next_stage) = crop.next_stage_at {
             lines.push(format!(
                 "  next: {:.1}",
-                next_stage - simulation_time.elapsed_seconds_f32()
+                next_stage
+                    .duration_since(simulation_time.now())
+                    .as_secs_f32()
             ));
         }
     }
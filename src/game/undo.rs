@@ -0,0 +1,572 @@
+use std::collections::VecDeque;
+
+use bevy::app::{App, Plugin, Update};
+use bevy::ecs::system::SystemParam;
+use bevy::log::{error, warn};
+use bevy::prelude::{
+    in_state, Commands, DespawnRecursiveExt, Entity, EventWriter, IntoSystemConfigs, Query, Res,
+    ResMut, Resource,
+};
+use bevy::utils::HashMap;
+use leafwing_input_manager::action_state::ActionState;
+
+use data::prelude::ItemId;
+
+use crate::game::crops::spawn_crop_entity;
+use crate::game::drops::{add_drop_to_world, DropSpawnParams};
+use crate::game::props::spawn_prop_entity;
+use crate::game::walls::{
+    build_and_spawn_door_entity, build_and_spawn_wall_entity, WallChangedEvent,
+};
+use crate::prelude::chunk_data::{CropData, DropData, DropId, PropData};
+use crate::prelude::loaded_chunks::LoadedChunks;
+use crate::prelude::simulation_time::SimulationTick;
+use crate::prelude::tile_data::EdgeStructure;
+use crate::prelude::update_tile_event::UpdateTileEvent;
+use crate::prelude::{
+    AddedDrop, CardinalDirection, ChunkPos, GameState, Inventory, MapPos, PlayerAction,
+    SimulationTime, WorldData,
+};
+
+/// Older steps get forgotten once the history grows beyond this.
+const MAX_UNDO_STEPS: usize = 100;
+
+pub struct UndoPlugin;
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UndoHistory>()
+            .add_systems(Update, undo_and_redo.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// A single reversible change to the world, remembering the state before and after it happened.
+pub enum WorldCommand {
+    SetTilled {
+        pos: MapPos,
+        before: bool,
+        after: bool,
+    },
+    SetWeeds {
+        pos: MapPos,
+        before: bool,
+        after: bool,
+    },
+    SetEdge {
+        pos: MapPos,
+        edge: CardinalDirection,
+        before: Option<EdgeStructure>,
+        after: Option<EdgeStructure>,
+    },
+    SetCrop {
+        pos: MapPos,
+        before: Option<CropData>,
+        after: Option<CropData>,
+        /// When the crop snapshots were taken, so their growth can be paused while they aren't in the world.
+        recorded_at: SimulationTick,
+    },
+    SetProp {
+        pos: MapPos,
+        before: Option<PropData>,
+        after: Option<PropData>,
+    },
+    ChangeInventory {
+        actor: Entity,
+        item_id: ItemId,
+        delta: i64,
+    },
+    /// Undoing this takes the drop back, or the same items from the actor if it already got picked up.
+    AddDrop {
+        actor: Entity,
+        chunk: ChunkPos,
+        id: DropId,
+        drop: DropData,
+    },
+}
+
+#[derive(Resource, Default)]
+pub struct UndoHistory {
+    undo_stack: VecDeque<Vec<WorldCommand>>,
+    redo_stack: Vec<Vec<WorldCommand>>,
+}
+
+impl UndoHistory {
+    /// Stores everything which happened at once as a single undo step.
+    pub fn record(&mut self, step: Vec<WorldCommand>) {
+        if step.is_empty() {
+            return;
+        }
+
+        self.redo_stack.clear();
+        self.undo_stack.push_back(step);
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum HistoryDirection {
+    Undo,
+    Redo,
+}
+
+impl HistoryDirection {
+    /// Picks the state which should be there after moving in this direction.
+    fn target<'a, T>(&self, before: &'a T, after: &'a T) -> &'a T {
+        match self {
+            HistoryDirection::Undo => before,
+            HistoryDirection::Redo => after,
+        }
+    }
+
+    /// Picks the state which should currently be there.
+    fn expected<'a, T>(&self, before: &'a T, after: &'a T) -> &'a T {
+        match self {
+            HistoryDirection::Undo => after,
+            HistoryDirection::Redo => before,
+        }
+    }
+
+    fn sign(&self) -> i64 {
+        match self {
+            HistoryDirection::Undo => -1,
+            HistoryDirection::Redo => 1,
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct HistoryParams<'w, 's> {
+    commands: Commands<'w, 's>,
+    world_data: ResMut<'w, WorldData>,
+    loaded_chunks: ResMut<'w, LoadedChunks>,
+    inventories: Query<'w, 's, &'static mut Inventory>,
+    drop_params: DropSpawnParams<'w, 's>,
+    update_tile_events: EventWriter<'w, UpdateTileEvent>,
+    wall_changed_events: EventWriter<'w, WallChangedEvent>,
+    simulation_time: Res<'w, SimulationTime>,
+}
+
+fn undo_and_redo(
+    action_state: Query<&ActionState<PlayerAction>>,
+    mut history: ResMut<UndoHistory>,
+    mut params: HistoryParams,
+) {
+    let action_state = action_state.get_single();
+    if action_state.is_err() {
+        error!("PlayerAction State was missing!");
+        return;
+    }
+    let action_state = action_state.unwrap();
+
+    let direction = if action_state.just_pressed(&PlayerAction::Undo) {
+        HistoryDirection::Undo
+    } else if action_state.just_pressed(&PlayerAction::Redo) {
+        HistoryDirection::Redo
+    } else {
+        return;
+    };
+
+    let step = match direction {
+        HistoryDirection::Undo => history.undo_stack.pop_back(),
+        HistoryDirection::Redo => history.redo_stack.pop(),
+    };
+    let Some(mut step) = step else {
+        return;
+    };
+
+    // Whatever happened afterwards and wasn't recorded would get overwritten, so the step is dropped instead.
+    // Keeping it around would block everything below it forever.
+    if !can_apply(&step, direction, &params) {
+        warn!("Unable to {:?}, the world has changed since.", direction);
+        return;
+    }
+
+    match direction {
+        HistoryDirection::Undo => {
+            for command in step.iter_mut().rev() {
+                apply(command, direction, &mut params);
+            }
+            history.redo_stack.push(step);
+        }
+        HistoryDirection::Redo => {
+            for command in step.iter_mut() {
+                apply(command, direction, &mut params);
+            }
+            history.undo_stack.push_back(step);
+        }
+    }
+}
+
+/// Checks whether the world still looks like it did right after the step, and all needed items are still around.
+fn can_apply(step: &[WorldCommand], direction: HistoryDirection, params: &HistoryParams) -> bool {
+    let world_data = &params.world_data;
+    let mut inventory_changes: HashMap<(Entity, ItemId), i64> = HashMap::new();
+    let mut taken_from_drops: HashMap<(ChunkPos, DropId), (ItemId, u32, Entity)> = HashMap::new();
+
+    for command in step {
+        match command {
+            WorldCommand::SetTilled { pos, before, after } => {
+                let Some(chunk) = world_data.chunks.get(&pos.chunk) else {
+                    return false;
+                };
+                if chunk.at_pos(&pos.tile).is_tilled != *direction.expected(before, after) {
+                    return false;
+                }
+            }
+            WorldCommand::SetWeeds { pos, before, after } => {
+                let Some(chunk) = world_data.chunks.get(&pos.chunk) else {
+                    return false;
+                };
+                if chunk.at_pos(&pos.tile).has_weeds != *direction.expected(before, after) {
+                    return false;
+                }
+            }
+            WorldCommand::SetEdge {
+                pos,
+                edge,
+                before,
+                after,
+            } => {
+                let Some(chunk) = world_data.chunks.get(&pos.chunk) else {
+                    return false;
+                };
+                if chunk.at_pos(&pos.tile).walls.at(*edge) != *direction.expected(before, after) {
+                    return false;
+                }
+            }
+            WorldCommand::SetCrop {
+                pos, before, after, ..
+            } => {
+                let Some(chunk) = world_data.chunks.get(&pos.chunk) else {
+                    return false;
+                };
+                // Crops keep growing, so only their kind needs to match
+                let current = chunk.crops.get(&pos.tile).map(|crop| crop.crop_id);
+                let expected = direction.expected(before, after).as_ref();
+                if current != expected.map(|crop| crop.crop_id) {
+                    return false;
+                }
+            }
+            WorldCommand::SetProp { pos, before, after } => {
+                let Some(chunk) = world_data.chunks.get(&pos.chunk) else {
+                    return false;
+                };
+                let current = chunk.props.get(&pos.tile);
+                let expected = direction.expected(before, after).as_ref();
+                let matches = match (current, expected) {
+                    (Some(current), Some(expected)) => {
+                        current.prop_id == expected.prop_id
+                            && current.inventory == expected.inventory
+                    }
+                    (None, None) => true,
+                    _ => false,
+                };
+                if !matches {
+                    return false;
+                }
+            }
+            WorldCommand::ChangeInventory {
+                actor,
+                item_id,
+                delta,
+            } => {
+                *inventory_changes.entry((*actor, *item_id)).or_default() +=
+                    delta * direction.sign();
+            }
+            WorldCommand::AddDrop {
+                actor,
+                chunk,
+                id,
+                drop,
+            } => {
+                if direction == HistoryDirection::Undo {
+                    let entry =
+                        taken_from_drops
+                            .entry((*chunk, *id))
+                            .or_insert((drop.item_id, 0, *actor));
+                    entry.1 += drop.amount;
+                }
+            }
+        }
+    }
+
+    // Drops which already got picked up need to be paid back from the actor's inventory
+    for ((chunk, id), (item_id, amount, actor)) in taken_from_drops {
+        if !is_drop_available(world_data, chunk, id, item_id, amount) {
+            *inventory_changes.entry((actor, item_id)).or_default() -= amount as i64;
+        }
+    }
+
+    inventory_changes.iter().all(|((actor, item_id), change)| {
+        params.inventories.get(*actor).map_or(false, |inventory| {
+            inventory.item_count(*item_id) as i64 + change >= 0
+        })
+    })
+}
+
+fn is_drop_available(
+    world_data: &WorldData,
+    chunk: ChunkPos,
+    id: DropId,
+    item_id: ItemId,
+    amount: u32,
+) -> bool {
+    world_data
+        .chunks
+        .get(&chunk)
+        .and_then(|chunk| chunk.drops.get(&id))
+        .map_or(false, |drop| {
+            drop.item_id == item_id && drop.amount >= amount
+        })
+}
+
+fn apply(command: &mut WorldCommand, direction: HistoryDirection, params: &mut HistoryParams) {
+    match command {
+        WorldCommand::SetTilled { pos, before, after } => {
+            let Some(chunk) = params.world_data.chunks.get_mut(&pos.chunk) else {
+                return;
+            };
+            chunk.set_at_pos(&pos.tile, *direction.target(before, after));
+
+            if params.loaded_chunks.chunks.contains_key(&pos.chunk) {
+                params
+                    .update_tile_events
+                    .send(UpdateTileEvent::new(pos.chunk, pos.tile));
+                params
+                    .update_tile_events
+                    .send_batch(UpdateTileEvent::surrounding_tiles(pos.chunk, pos.tile));
+            }
+        }
+        WorldCommand::SetWeeds { pos, before, after } => {
+            let Some(chunk) = params.world_data.chunks.get_mut(&pos.chunk) else {
+                return;
+            };
+            chunk.at_pos_mut(&pos.tile).has_weeds = *direction.target(before, after);
+
+            if params.loaded_chunks.chunks.contains_key(&pos.chunk) {
+                params
+                    .update_tile_events
+                    .send(UpdateTileEvent::new(pos.chunk, pos.tile));
+            }
+        }
+        WorldCommand::SetEdge {
+            pos,
+            edge,
+            before,
+            after,
+        } => {
+            let Some(chunk) = params.world_data.chunks.get_mut(&pos.chunk) else {
+                return;
+            };
+            let target = *direction.target(before, after);
+            let previous = chunk.at_pos(&pos.tile).walls.at(*edge);
+            chunk.at_pos_mut(&pos.tile).walls.set_at(*edge, target);
+            params.wall_changed_events.send(WallChangedEvent {
+                pos: *pos,
+                edge: *edge,
+                is_door_toggle: matches!(
+                    (previous, target),
+                    (
+                        Some(EdgeStructure::Door { .. }),
+                        Some(EdgeStructure::Door { .. })
+                    )
+                ),
+            });
+
+            // Removed structures despawn themselves, and doors keep track of being opened or closed
+            if previous.is_some() {
+                return;
+            }
+            let Some(tile) = params
+                .loaded_chunks
+                .chunks
+                .get(&pos.chunk)
+                .and_then(|loaded_data| loaded_data.get_tile(pos.tile.x, pos.tile.y))
+            else {
+                return;
+            };
+
+            match target {
+                Some(EdgeStructure::Wall { structure_id }) => {
+                    let Some(definition) = params
+                        .drop_params
+                        .all_items
+                        .edge_structures
+                        .get(&structure_id)
+                    else {
+                        return;
+                    };
+                    build_and_spawn_wall_entity(
+                        &mut params.commands,
+                        tile,
+                        *pos,
+                        *edge,
+                        definition,
+                    );
+                }
+                Some(EdgeStructure::Door { is_open }) => {
+                    build_and_spawn_door_entity(
+                        &mut params.commands,
+                        tile,
+                        *pos,
+                        *edge,
+                        is_open,
+                        &params.drop_params.meshes,
+                        &params.drop_params.materials,
+                    );
+                }
+                None => {}
+            }
+        }
+        WorldCommand::SetCrop {
+            pos,
+            before,
+            after,
+            recorded_at,
+        } => {
+            let Some(chunk) = params.world_data.chunks.get_mut(&pos.chunk) else {
+                return;
+            };
+            let now = params.simulation_time.now();
+            let paused_for = now.duration_since(*recorded_at);
+
+            // Remember how far the current crop has grown, in case we come back to it later
+            let current = chunk.crops.remove(&pos.tile);
+            let (target, current_slot) = match direction {
+                HistoryDirection::Undo => (before.clone(), after),
+                HistoryDirection::Redo => (after.clone(), before),
+            };
+            if current.is_some() {
+                *current_slot = current;
+            }
+            *recorded_at = now;
+
+            if let Some(loaded_data) = params.loaded_chunks.chunks.get_mut(&pos.chunk) {
+                if let Some(entity) = loaded_data.crops.remove(&pos.tile) {
+                    params.commands.entity(entity).despawn_recursive();
+                }
+            }
+
+            let Some(mut crop) = target else {
+                return;
+            };
+            crop.next_stage_at = crop.next_stage_at.map(|time| time.after(paused_for));
+
+            if let Some(loaded_data) = params.loaded_chunks.chunks.get_mut(&pos.chunk) {
+                if let Some(definition) = params.drop_params.all_items.crops.get(&crop.crop_id) {
+                    let entity = spawn_crop_entity(
+                        &mut params.commands,
+                        &mut params.drop_params.sprite_params,
+                        loaded_data.chunk_parent,
+                        pos,
+                        definition,
+                        crop.stage,
+                    );
+                    loaded_data.crops.insert(pos.tile, entity);
+                }
+            }
+            chunk.crops.insert(pos.tile, crop);
+        }
+        WorldCommand::SetProp { pos, before, after } => {
+            let Some(chunk) = params.world_data.chunks.get_mut(&pos.chunk) else {
+                return;
+            };
+
+            let target = direction.target(before, after).clone();
+            let had_prop = chunk.props.remove(&pos.tile).is_some();
+            if let Some(loaded_data) = params.loaded_chunks.chunks.get_mut(&pos.chunk) {
+                if had_prop {
+                    if let Some(entity) = loaded_data.props.remove(&pos.tile) {
+                        params.commands.entity(entity).despawn_recursive();
+                    }
+                }
+
+                if let Some(definition) = target
+                    .as_ref()
+                    .and_then(|prop| params.drop_params.all_items.props.get(&prop.prop_id))
+                {
+                    let entity = spawn_prop_entity(
+                        &mut params.commands,
+                        loaded_data.chunk_parent,
+                        pos,
+                        definition,
+                    );
+                    loaded_data.props.insert(pos.tile, entity);
+                }
+            }
+
+            if let Some(prop) = target {
+                chunk.props.insert(pos.tile, prop);
+            }
+        }
+        WorldCommand::ChangeInventory {
+            actor,
+            item_id,
+            delta,
+        } => {
+            let Ok(mut inventory) = params.inventories.get_mut(*actor) else {
+                return;
+            };
+
+            let change = *delta * direction.sign();
+            if change > 0 {
+                inventory.add_item(item_id, change as u32);
+            } else {
+                inventory.remove_item(item_id, change.unsigned_abs() as u32);
+            }
+        }
+        WorldCommand::AddDrop {
+            actor,
+            chunk,
+            id,
+            drop,
+        } => match direction {
+            HistoryDirection::Undo => {
+                if !is_drop_available(&params.world_data, *chunk, *id, drop.item_id, drop.amount) {
+                    if let Ok(mut inventory) = params.inventories.get_mut(*actor) {
+                        inventory.remove_item(&drop.item_id, drop.amount);
+                    }
+                    return;
+                }
+
+                let Some(chunk_data) = params.world_data.chunks.get_mut(chunk) else {
+                    return;
+                };
+                let Some(existing) = chunk_data.drops.get_mut(id) else {
+                    return;
+                };
+
+                existing.amount -= drop.amount;
+                if existing.amount == 0 {
+                    chunk_data.drops.remove(id);
+                    if let Some(entity) = params
+                        .loaded_chunks
+                        .chunks
+                        .get_mut(chunk)
+                        .and_then(|loaded_data| loaded_data.drops.remove(id))
+                    {
+                        params.commands.entity(entity).despawn_recursive();
+                    }
+                }
+            }
+            HistoryDirection::Redo => {
+                let added = add_drop_to_world(
+                    &mut params.commands,
+                    &mut params.drop_params,
+                    &mut params.world_data,
+                    &mut params.loaded_chunks,
+                    drop.clone(),
+                );
+
+                if let Some(
+                    AddedDrop::New(new_chunk, new_id) | AddedDrop::Merged(new_chunk, new_id),
+                ) = added
+                {
+                    *chunk = new_chunk;
+                    *id = new_id;
+                }
+            }
+        },
+    }
+}
@@ -1,24 +1,44 @@
 use bevy::app::App;
 use bevy::core::Name;
-use bevy::math::{Quat, Vec3};
+use bevy::math::{IVec2, Quat, Vec3};
 use bevy::prelude::{
-    default, in_state, BuildChildren, Commands, Component, Entity, Handle, IntoSystemConfigs, Mesh,
-    OnEnter, PbrBundle, Plugin, Query, Res, SpatialBundle, StandardMaterial, Transform, With,
+    default, in_state, on_event, BuildChildren, Commands, Component, Condition,
+    DespawnRecursiveExt, Entity, Event, EventReader, GlobalTransform, Handle, IntoSystemConfigs,
+    OnEnter, PbrBundle, Plugin, Query, Res, SpatialBundle, StandardMaterial, Time, Transform,
+    Update, With,
 };
+use bevy::utils::{HashMap, HashSet};
 
+use data::prelude::{AllItems, EdgeStructureDefinition, EdgeStructureId};
+
+use crate::game::camera::{CameraFocus, MainCamera};
 use crate::game::debug_actions::DebugWallVisibilityState;
-use crate::prelude::{CardinalDirection, DebugMaterials, DebugMeshes};
+use crate::game::rooms::Rooms;
+use crate::prelude::helpers::neighbour_in_direction;
+use crate::prelude::loaded_chunks::{ChunkLoadedEvent, ChunkUnloadedEvent, LoadedChunks};
+use crate::prelude::tile_data::EdgeStructure;
+use crate::prelude::{
+    CardinalDirection, ChunkPos, DebugMaterials, DebugMeshes, MapPos, TilePos, WorldData,
+    CHUNK_SIZE,
+};
 use crate::GameState;
 
 const TILE_EDGE: f32 = 0.5;
 const WALL_WIDTH: f32 = 0.1;
 
+/// Placed on every built edge structure, previews excluded.
 #[derive(Component)]
-struct WallMarker;
+struct WallMarker {
+    pos: MapPos,
+    edge: CardinalDirection,
+    material: Handle<StandardMaterial>,
+    blocks_sight: bool,
+}
 
 pub struct WallPlugin;
 impl Plugin for WallPlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<WallChangedEvent>();
         app.add_systems(
             OnEnter(DebugWallVisibilityState::Visible),
             show_walls.run_if(in_state(GameState::Playing)),
@@ -27,24 +47,378 @@ impl Plugin for WallPlugin {
             OnEnter(DebugWallVisibilityState::Hidden),
             hide_walls.run_if(in_state(GameState::Playing)),
         );
+        app.add_systems(
+            Update,
+            (
+                (sync_doors, despawn_demolished_walls).run_if(on_event::<WallChangedEvent>()),
+                animate_doors.after(sync_doors),
+                update_edge_posts.run_if(
+                    on_event::<WallChangedEvent>()
+                        .or_else(on_event::<ChunkLoadedEvent>())
+                        .or_else(on_event::<ChunkUnloadedEvent>()),
+                ),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+        app.add_systems(
+            Update,
+            fade_occluding_walls
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(DebugWallVisibilityState::Visible)),
+        );
     }
 }
 
-fn hide_walls(
+/// Sent whenever a wall has been placed or removed, or a door has been opened or closed.
+#[derive(Event, Debug)]
+pub struct WallChangedEvent {
+    pub pos: MapPos,
+    pub edge: CardinalDirection,
+    /// Doors enclose rooms no matter whether they are open, so toggling them doesn't change any room.
+    pub is_door_toggle: bool,
+}
+
+const DOOR_WIDTH: f32 = 0.9;
+const DOOR_HEIGHT: f32 = 1.8;
+const DOOR_OPEN_ANGLE: f32 = std::f32::consts::FRAC_PI_2;
+const DOOR_ROTATION_SPEED: f32 = 6.0;
+
+/// The hinge of a door, rotating the actual door mesh around its edge.
+#[derive(Component)]
+pub struct Door {
+    pub pos: MapPos,
+    pub edge: CardinalDirection,
+    pub is_open: bool,
+    angle: f32,
+}
+
+/// Returns the structure on this edge and where it is stored inside [WorldData], as it might belong to either adjacent tile.
+pub fn find_edge_structure(
+    world_data: &WorldData,
+    pos: MapPos,
+    edge: CardinalDirection,
+) -> Option<(MapPos, CardinalDirection, EdgeStructure)> {
+    let (chunk_pos, tile_pos) = neighbour_in_direction(&pos.chunk, &pos.tile, edge);
+    [
+        (pos, edge),
+        (MapPos::new(chunk_pos, tile_pos), edge.opposite()),
+    ]
+    .into_iter()
+    .find_map(|(pos, edge)| {
+        let chunk = world_data.chunks.get(&pos.chunk)?;
+        let structure = chunk.at_pos(&pos.tile).walls.at(edge)?;
+        Some((pos, edge, structure))
+    })
+}
+
+pub fn build_and_spawn_door_entity(
+    commands: &mut Commands,
+    tile: Entity,
+    pos: MapPos,
+    tile_edge: CardinalDirection,
+    is_open: bool,
+    debug_meshes: &DebugMeshes,
+    debug_materials: &DebugMaterials,
+) -> Entity {
+    let rotation = tile_edge_to_rotation(tile_edge);
+    let hinge_position = tile_edge_to_position(tile_edge, WALL_WIDTH)
+        + rotation * Vec3::new(-DOOR_WIDTH * 0.5, 0.0, 0.0);
+    let angle = if is_open { DOOR_OPEN_ANGLE } else { 0.0 };
+
+    let door = commands
+        .spawn((
+            Name::new("Door"),
+            PbrBundle {
+                mesh: debug_meshes.door.clone(),
+                material: debug_materials.door.clone(),
+                transform: Transform::from_xyz(DOOR_WIDTH * 0.5, DOOR_HEIGHT * 0.5, 0.0),
+                ..default()
+            },
+        ))
+        .id();
+
+    commands
+        .spawn((
+            Name::new("Door Hinge"),
+            SpatialBundle {
+                transform: Transform {
+                    translation: hinge_position,
+                    rotation: rotation * Quat::from_rotation_y(angle),
+                    ..default()
+                },
+                ..default()
+            },
+            Door {
+                pos,
+                edge: tile_edge,
+                is_open,
+                angle,
+            },
+        ))
+        .set_parent(tile)
+        .add_child(door)
+        .id()
+}
+
+fn sync_doors(
+    mut commands: Commands,
+    world_data: Res<WorldData>,
+    mut events: EventReader<WallChangedEvent>,
+    mut doors: Query<(Entity, &mut Door)>,
+) {
+    for event in events.read() {
+        for (entity, mut door) in doors.iter_mut() {
+            if door.pos != event.pos || door.edge != event.edge {
+                continue;
+            }
+
+            let Some(chunk) = world_data.chunks.get(&door.pos.chunk) else {
+                continue;
+            };
+
+            if let Some(EdgeStructure::Door { is_open }) =
+                chunk.at_pos(&door.pos.tile).walls.at(door.edge)
+            {
+                door.is_open = is_open;
+            } else {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn despawn_demolished_walls(
+    mut commands: Commands,
+    world_data: Res<WorldData>,
+    mut events: EventReader<WallChangedEvent>,
+    walls: Query<(Entity, &WallMarker)>,
+) {
+    for event in events.read() {
+        let is_demolished = world_data
+            .chunks
+            .get(&event.pos.chunk)
+            .map_or(true, |chunk| {
+                chunk.at_pos(&event.pos.tile).walls.at(event.edge).is_none()
+            });
+        if !is_demolished {
+            continue;
+        }
+
+        for (entity, marker) in walls.iter() {
+            if marker.pos == event.pos && marker.edge == event.edge {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn animate_doors(time: Res<Time>, mut doors: Query<(&mut Door, &mut Transform)>) {
+    for (mut door, mut transform) in doors.iter_mut() {
+        let target = if door.is_open { DOOR_OPEN_ANGLE } else { 0.0 };
+        if door.angle == target {
+            continue;
+        }
+
+        let step = DOOR_ROTATION_SPEED * time.delta_seconds();
+        door.angle = if door.angle < target {
+            (door.angle + step).min(target)
+        } else {
+            (door.angle - step).max(target)
+        };
+        transform.rotation = tile_edge_to_rotation(door.edge) * Quat::from_rotation_y(door.angle);
+    }
+}
+
+/// How far in front of the camera focus walls get faded out.
+const CUTAWAY_DISTANCE: f32 = 3.0;
+/// How far to the sides of the line between camera focus and camera walls get faded out.
+const CUTAWAY_HALF_WIDTH: f32 = 1.5;
+
+/// Fades out all walls standing between the camera and whatever it is focusing on,
+/// as well as all walls of the room it is standing in.
+fn fade_occluding_walls(
     materials: Res<DebugMaterials>,
-    mut query: Query<&mut Handle<StandardMaterial>, With<WallMarker>>,
+    rooms: Res<Rooms>,
+    camera: Query<&GlobalTransform, With<MainCamera>>,
+    camera_focus: Query<&GlobalTransform, With<CameraFocus>>,
+    mut walls: Query<(&GlobalTransform, &WallMarker, &mut Handle<StandardMaterial>)>,
 ) {
-    for mut material in query.iter_mut() {
-        *material = materials.wall_hidden.clone();
+    let (Ok(camera), Ok(camera_focus)) = (camera.get_single(), camera_focus.get_single()) else {
+        return;
+    };
+
+    let back = camera.back();
+    let Some(towards_camera) = Vec3::new(back.x, 0.0, back.z).try_normalize() else {
+        return;
+    };
+    let focus = camera_focus.translation();
+    let current_room = rooms.room_at(&MapPos::from_world_pos(focus));
+
+    for (transform, marker, mut material) in walls.iter_mut() {
+        if !marker.blocks_sight {
+            continue;
+        }
+
+        let offset = transform.translation() - focus;
+        let offset = Vec3::new(offset.x, 0.0, offset.z);
+        let distance = offset.dot(towards_camera);
+        let sideways = (offset - towards_camera * distance).length();
+
+        let is_occluding =
+            distance > -TILE_EDGE && distance < CUTAWAY_DISTANCE && sideways < CUTAWAY_HALF_WIDTH;
+
+        // Walls are stored on either side, so the room might be on the neighbouring tile
+        let is_in_current_room = current_room.is_some_and(|(_, room)| {
+            let (chunk_pos, tile_pos) =
+                neighbour_in_direction(&marker.pos.chunk, &marker.pos.tile, marker.edge);
+            room.tiles.contains(&marker.pos)
+                || room.tiles.contains(&MapPos::new(chunk_pos, tile_pos))
+        });
+
+        let wanted = if is_occluding || is_in_current_room {
+            &materials.wall_faded
+        } else {
+            &marker.material
+        };
+
+        if *material != *wanted {
+            *material = wanted.clone();
+        }
+    }
+}
+
+/// A post standing on a tile corner, shared by all edge structures touching it.
+#[derive(Component)]
+struct EdgePost {
+    corner: IVec2,
+    chunk: ChunkPos,
+    structure_id: EdgeStructureId,
+}
+
+/// The two tile corners at the ends of the given edge, in global tile coordinates.
+/// Corner (x, y) is the north western corner of tile (x, y).
+fn edge_corners(tile: IVec2, edge: CardinalDirection) -> [IVec2; 2] {
+    match edge {
+        CardinalDirection::North => [tile, tile + IVec2::X],
+        CardinalDirection::East => [tile + IVec2::X, tile + IVec2::ONE],
+        CardinalDirection::South => [tile + IVec2::Y, tile + IVec2::ONE],
+        CardinalDirection::West => [tile, tile + IVec2::Y],
+    }
+}
+
+/// Places a single post on every tile corner touched by an edge structure with posts inside the loaded chunks.
+fn update_edge_posts(
+    mut commands: Commands,
+    world_data: Res<WorldData>,
+    loaded_chunks: Res<LoadedChunks>,
+    all_items: Res<AllItems>,
+    posts: Query<(Entity, &EdgePost)>,
+) {
+    // Corners shared by different structures get the post of the one with the lowest ID
+    let mut wanted: HashMap<IVec2, (EdgeStructureId, MapPos)> = HashMap::new();
+    for chunk_pos in loaded_chunks.chunks.keys() {
+        let Some(chunk) = world_data.chunks.get(chunk_pos) else {
+            continue;
+        };
+
+        for y in 0..CHUNK_SIZE as u32 {
+            for x in 0..CHUNK_SIZE as u32 {
+                let pos = MapPos::new(*chunk_pos, TilePos::new(x, y));
+                for edge in CardinalDirection::ALL {
+                    let Some(EdgeStructure::Wall { structure_id }) = chunk.at(x, y).walls.at(edge)
+                    else {
+                        continue;
+                    };
+                    let has_posts = all_items
+                        .edge_structures
+                        .get(&structure_id)
+                        .is_some_and(|definition| definition.post.is_some());
+                    if !has_posts {
+                        continue;
+                    }
+
+                    for corner in edge_corners(pos.global_tile_pos(), edge) {
+                        let post = wanted.entry(corner).or_insert((structure_id, pos));
+                        if structure_id.0 < post.0 .0 {
+                            *post = (structure_id, pos);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut existing = HashSet::new();
+    for (entity, post) in posts.iter() {
+        // Posts inside unloaded chunks are despawned together with their chunk
+        if !loaded_chunks.chunks.contains_key(&post.chunk) {
+            continue;
+        }
+
+        let is_wanted = wanted
+            .get(&post.corner)
+            .is_some_and(|(structure_id, _)| *structure_id == post.structure_id);
+        if is_wanted {
+            existing.insert(post.corner);
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for (corner, (structure_id, pos)) in wanted {
+        if existing.contains(&corner) {
+            continue;
+        }
+        let Some(definition) = all_items.edge_structures.get(&structure_id) else {
+            continue;
+        };
+        let Some(post) = &definition.post else {
+            continue;
+        };
+        let Some(loaded_data) = loaded_chunks.chunks.get(&pos.chunk) else {
+            continue;
+        };
+
+        // Tile origins are at their center, while the corner is relative to the tile's north western corner
+        let offset = corner - pos.global_tile_pos();
+        let translation = pos.pos_inside_chunk(post.height * 0.5)
+            + Vec3::new(
+                offset.x as f32 - TILE_EDGE,
+                0.0,
+                offset.y as f32 - TILE_EDGE,
+            );
+
+        commands
+            .spawn((
+                Name::new("Post"),
+                PbrBundle {
+                    mesh: post.mesh.clone(),
+                    material: definition.material.clone(),
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+                EdgePost {
+                    corner,
+                    chunk: pos.chunk,
+                    structure_id,
+                },
+            ))
+            .set_parent(loaded_data.chunk_parent);
     }
 }
 
-fn show_walls(
+fn hide_walls(
     materials: Res<DebugMaterials>,
     mut query: Query<&mut Handle<StandardMaterial>, With<WallMarker>>,
 ) {
     for mut material in query.iter_mut() {
-        *material = materials.wall.clone();
+        *material = materials.wall_hidden.clone();
+    }
+}
+
+fn show_walls(mut query: Query<(&WallMarker, &mut Handle<StandardMaterial>)>) {
+    for (marker, mut material) in query.iter_mut() {
+        *material = marker.material.clone();
     }
 }
 
@@ -52,50 +426,89 @@ pub fn build_and_spawn_wall_entity_with_mesh_and_material(
     commands: &mut Commands,
     tile: Entity,
     tile_edge: CardinalDirection,
-    mesh: Handle<Mesh>,
+    definition: &EdgeStructureDefinition,
     material: Handle<StandardMaterial>,
 ) -> Entity {
-    return commands
+    let entity = commands
         .spawn((
-            Name::new("Wall"),
+            Name::new(definition.name.clone()),
             PbrBundle {
-                mesh,
-                material,
+                mesh: definition.mesh.clone(),
+                material: material.clone(),
                 transform: Transform {
-                    translation: tile_edge_to_position(tile_edge),
+                    translation: tile_edge_to_position(tile_edge, definition.thickness)
+                        + Vec3::Y * definition.height * 0.5,
                     rotation: tile_edge_to_rotation(tile_edge),
                     ..default()
                 },
                 ..default()
             },
-            WallMarker,
         ))
         .set_parent(tile)
         .id();
+
+    entity
 }
 
 pub fn build_and_spawn_wall_entity(
     commands: &mut Commands,
     tile: Entity,
+    pos: MapPos,
     tile_edge: CardinalDirection,
-    debug_meshes: &DebugMeshes,
-    debug_materials: &DebugMaterials,
+    definition: &EdgeStructureDefinition,
 ) -> Entity {
-    build_and_spawn_wall_entity_with_mesh_and_material(
+    let entity = build_and_spawn_wall_entity_with_mesh_and_material(
         commands,
         tile,
         tile_edge,
-        debug_meshes.wall.clone(),
-        debug_materials.wall.clone(),
-    )
+        definition,
+        definition.material.clone(),
+    );
+
+    // Previews use the same meshes, but shouldn't be affected by wall visibility changes
+    commands.entity(entity).insert(WallMarker {
+        pos,
+        edge: tile_edge,
+        material: definition.material.clone(),
+        blocks_sight: definition.blocks_sight,
+    });
+    entity
+}
+
+pub fn build_and_spawn_door_preview(
+    commands: &mut Commands,
+    tile: Entity,
+    tile_edge: CardinalDirection,
+    debug_meshes: &DebugMeshes,
+    material: Handle<StandardMaterial>,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new("Door Preview"),
+            PbrBundle {
+                mesh: debug_meshes.door.clone(),
+                material,
+                transform: Transform {
+                    translation: tile_edge_to_position(tile_edge, WALL_WIDTH)
+                        + Vec3::Y * DOOR_HEIGHT * 0.5,
+                    rotation: tile_edge_to_rotation(tile_edge),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .set_parent(tile)
+        .id()
 }
 
-fn tile_edge_to_position(cardinal_direction: CardinalDirection) -> Vec3 {
+/// The center of the given tile edge on the ground, moved inwards so structures of this thickness stay inside the tile.
+fn tile_edge_to_position(cardinal_direction: CardinalDirection, thickness: f32) -> Vec3 {
+    let inset = TILE_EDGE - thickness * 0.5;
     match cardinal_direction {
-        CardinalDirection::North => Vec3::new(0.0, 1.0, -TILE_EDGE + WALL_WIDTH * 0.5),
-        CardinalDirection::East => Vec3::new(TILE_EDGE - WALL_WIDTH * 0.5, 1.0, 0.0),
-        CardinalDirection::South => Vec3::new(0.0, 1.0, TILE_EDGE - WALL_WIDTH * 0.5),
-        CardinalDirection::West => Vec3::new(-TILE_EDGE + WALL_WIDTH * 0.5, 1.0, 0.0),
+        CardinalDirection::North => Vec3::new(0.0, 0.0, -inset),
+        CardinalDirection::East => Vec3::new(inset, 0.0, 0.0),
+        CardinalDirection::South => Vec3::new(0.0, 0.0, inset),
+        CardinalDirection::West => Vec3::new(-inset, 0.0, 0.0),
     }
 }
 
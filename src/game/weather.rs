@@ -0,0 +1,52 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::{in_state, on_event, IntoSystemConfigs, Res, ResMut, Resource};
+use rand::Rng;
+
+use crate::game::chunk_simulation::simulate_world;
+use crate::game::rooms::Rooms;
+use crate::prelude::simulation_time::DayStartedEvent;
+use crate::prelude::{ChunkPos, GameState, MapPos, TilePos, WorldData, CHUNK_SIZE};
+
+const RAIN_CHANCE: f64 = 0.25;
+
+pub struct WeatherPlugin;
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>().add_systems(
+            Update,
+            start_new_weather_day
+                .after(simulate_world)
+                .run_if(in_state(GameState::Playing))
+                .run_if(on_event::<DayStartedEvent>()),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Weather {
+    pub is_raining: bool,
+}
+
+/// Rolls the weather for the new day. Rain waters all tilled tiles which aren't covered by a roof.
+fn start_new_weather_day(
+    mut weather: ResMut<Weather>,
+    mut world_data: ResMut<WorldData>,
+    rooms: Res<Rooms>,
+) {
+    weather.is_raining = rand::thread_rng().gen_bool(RAIN_CHANCE);
+
+    for (chunk_pos, chunk) in world_data.chunks.iter_mut() {
+        for y in 0..CHUNK_SIZE as u32 {
+            for x in 0..CHUNK_SIZE as u32 {
+                let tile = chunk.at_mut(x, y);
+                tile.is_watered = weather.is_raining
+                    && tile.is_tilled
+                    && !is_covered(&rooms, chunk_pos, TilePos::new(x, y));
+            }
+        }
+    }
+}
+
+fn is_covered(rooms: &Rooms, chunk_pos: &ChunkPos, tile_pos: TilePos) -> bool {
+    rooms.room_at(&MapPos::new(*chunk_pos, tile_pos)).is_some()
+}
@@ -1,19 +1,46 @@
+use bevy::math::Vec3;
 use bevy::utils::HashMap;
 
-use data::prelude::{CropDefinition, CropId};
+use data::prelude::{CropDefinition, CropId, ItemId, PropDefinition, PropId};
 
+use crate::prelude::simulation_time::SimulationTick;
 use crate::prelude::tile_data::TileData;
-use crate::prelude::SimulationTime;
+use crate::prelude::{Inventory, SimulationTime};
 use crate::prelude::{TilePos, CHUNK_SIZE};
 
 pub struct ChunkData {
     pub tiles: [TileData; CHUNK_SIZE * CHUNK_SIZE],
     pub crops: HashMap<TilePos, CropData>,
+    pub props: HashMap<TilePos, PropData>,
+    pub drops: HashMap<DropId, DropData>,
+    /// Chunks which aren't loaded stand still until they get caught up to the current time.
+    pub last_simulated: SimulationTick,
+    next_drop_id: DropId,
 }
 
+pub type DropId = u32;
+
+/// An item lying around in the world, waiting to be picked up.
+#[derive(Clone, Debug)]
+pub struct DropData {
+    pub item_id: ItemId,
+    pub amount: u32,
+    /// World position, kept in sync with the entity while the chunk is loaded.
+    pub position: Vec3,
+    /// Magnets ignore this drop until then, so dropped items aren't picked up again right away.
+    pub pickup_blocked_until: SimulationTick,
+}
+
+impl DropData {
+    pub fn can_be_picked_up(&self, now: SimulationTick) -> bool {
+        now >= self.pickup_blocked_until
+    }
+}
+
+#[derive(Clone)]
 pub struct CropData {
     pub crop_id: CropId,
-    pub next_stage_at: Option<f32>,
+    pub next_stage_at: Option<SimulationTick>,
     pub stage: u8,
 }
 
@@ -21,14 +48,32 @@ impl CropData {
     pub fn new(from: &CropDefinition, simulation_time: &SimulationTime) -> Self {
         Self {
             crop_id: from.id.clone(),
-            next_stage_at: Some(
-                simulation_time.elapsed_seconds_f32() + from.growth_time_per_stage as f32,
-            ),
+            next_stage_at: Some(simulation_time.now().after(from.growth_time_per_stage)),
             stage: 0,
         }
     }
 }
 
+#[derive(Clone)]
+pub struct PropData {
+    pub prop_id: PropId,
+    /// Items stored inside this prop, e.g. the contents of a shipping bin.
+    pub inventory: Option<Inventory>,
+}
+
+impl PropData {
+    pub fn new(from: &PropDefinition) -> Self {
+        Self {
+            prop_id: from.id,
+            inventory: if from.is_shipping_bin {
+                Some(Inventory::default())
+            } else {
+                None
+            },
+        }
+    }
+}
+
 impl ChunkData {
     pub fn at(&self, x: u32, y: u32) -> &TileData {
         &self.tiles[x as usize + y as usize * CHUNK_SIZE]
@@ -43,11 +88,21 @@ impl ChunkData {
         self.at_mut(pos.x, pos.y)
     }
     pub fn set_at(&mut self, x: u32, y: u32, value: bool) {
-        self.tiles[x as usize + y as usize * CHUNK_SIZE].is_tilled = value;
+        let tile = self.at_mut(x, y);
+        tile.is_tilled = value;
+        if !value {
+            tile.has_weeds = false;
+        }
     }
     pub fn set_at_pos(&mut self, pos: &TilePos, value: bool) {
         self.set_at(pos.x, pos.y, value);
     }
+    pub fn add_drop(&mut self, drop: DropData) -> DropId {
+        let id = self.next_drop_id;
+        self.next_drop_id = self.next_drop_id.wrapping_add(1);
+        self.drops.insert(id, drop);
+        id
+    }
 }
 
 impl Default for ChunkData {
@@ -55,6 +110,10 @@ impl Default for ChunkData {
         ChunkData {
             tiles: [TileData::default(); CHUNK_SIZE * CHUNK_SIZE],
             crops: HashMap::new(),
+            props: HashMap::new(),
+            drops: HashMap::new(),
+            last_simulated: SimulationTick::default(),
+            next_drop_id: 0,
         }
     }
 }
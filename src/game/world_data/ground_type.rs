@@ -9,4 +9,10 @@ impl GroundType {
             GroundType::Grass => 2,
         }
     }
+
+    pub fn is_walkable(&self) -> bool {
+        match self {
+            GroundType::Grass => true,
+        }
+    }
 }
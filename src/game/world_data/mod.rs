@@ -1,12 +1,17 @@
-use crate::prelude::chunk_data::ChunkData;
+use crate::prelude::chunk_data::{ChunkData, DropData, DropId, PropData};
+use crate::prelude::{Inventory, MapPos, TilePos};
 use bevy::prelude::{App, IVec2, Plugin, Resource};
 use bevy::utils::HashMap;
+use data::prelude::PropId;
 
 pub mod chunk_data;
 pub mod ground_type;
 pub mod tile_data;
 
-pub const DEBUG_WORLD_SIZE_MIN_AND_MAX: i32 = 1;
+pub const DEBUG_WORLD_SIZE_MIN_AND_MAX: i32 = 2;
+/// Drops of the same item which are closer than this get merged into a single stack.
+const DROP_MERGE_RADIUS: f32 = 1.5;
+const DEBUG_SHIPPING_BIN_PROP_ID: PropId = PropId(1);
 
 pub type ChunkPos = IVec2;
 
@@ -22,6 +27,41 @@ pub struct WorldData {
     pub chunks: HashMap<ChunkPos, ChunkData>,
 }
 
+pub enum AddedDrop {
+    New(ChunkPos, DropId),
+    /// The drop was added to the amount of an already existing, nearby drop.
+    Merged(ChunkPos, DropId),
+}
+
+impl WorldData {
+    /// Stores the drop inside the chunk at its position. Returns None if that chunk doesn't exist.
+    pub fn add_drop(&mut self, drop: DropData) -> Option<AddedDrop> {
+        let chunk_pos = MapPos::from_world_pos(drop.position).chunk;
+        let chunk = self.chunks.get_mut(&chunk_pos)?;
+
+        let nearby_stack = chunk.drops.iter_mut().find(|(_, existing)| {
+            existing.item_id == drop.item_id
+                && existing.position.distance(drop.position) < DROP_MERGE_RADIUS
+        });
+
+        if let Some((id, existing)) = nearby_stack {
+            existing.amount += drop.amount;
+            existing.pickup_blocked_until =
+                existing.pickup_blocked_until.max(drop.pickup_blocked_until);
+            return Some(AddedDrop::Merged(chunk_pos, *id));
+        }
+
+        Some(AddedDrop::New(chunk_pos, chunk.add_drop(drop)))
+    }
+
+    /// Like [WorldData::add_drop], but never merges the drop into existing stacks.
+    pub fn insert_drop(&mut self, drop: DropData) -> Option<(ChunkPos, DropId)> {
+        let chunk_pos = MapPos::from_world_pos(drop.position).chunk;
+        let chunk = self.chunks.get_mut(&chunk_pos)?;
+        Some((chunk_pos, chunk.add_drop(drop)))
+    }
+}
+
 impl Default for WorldData {
     fn default() -> Self {
         let mut result = WorldData {
@@ -36,6 +76,20 @@ impl Default for WorldData {
             }
         }
 
+        // FIXME: Should be placed by world generation once that exists
+        result
+            .chunks
+            .get_mut(&ChunkPos::new(0, 0))
+            .unwrap()
+            .props
+            .insert(
+                TilePos::new(3, 2),
+                PropData {
+                    prop_id: DEBUG_SHIPPING_BIN_PROP_ID,
+                    inventory: Some(Inventory::default()),
+                },
+            );
+
         result
     }
 }
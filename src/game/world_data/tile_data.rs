@@ -1,3 +1,5 @@
+use data::prelude::{AllItems, BuildCost, EdgeStructureId};
+
 use crate::prelude::ground_type::GroundType;
 use crate::prelude::CardinalDirection;
 
@@ -5,6 +7,10 @@ use crate::prelude::CardinalDirection;
 pub struct TileData {
     pub ground_type: GroundType,
     pub is_tilled: bool,
+    /// Set by rain at the start of a day, dries up again by the next one.
+    pub is_watered: bool,
+    /// Grows on tilled soil which is left empty. Has to be cut down before anything can be planted again.
+    pub has_weeds: bool,
     pub walls: TileWalls,
 }
 
@@ -13,21 +19,62 @@ impl Default for TileData {
         TileData {
             ground_type: GroundType::Grass,
             is_tilled: false,
+            is_watered: false,
+            has_weeds: false,
             walls: TileWalls::default(),
         }
     }
 }
 
+/// Something built on the edge between two tiles.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EdgeStructure {
+    Wall { structure_id: EdgeStructureId },
+    Door { is_open: bool },
+}
+
+impl EdgeStructure {
+    pub fn blocks_movement(&self, all_items: &AllItems) -> bool {
+        match self {
+            EdgeStructure::Wall { structure_id } => all_items
+                .edge_structures
+                .get(structure_id)
+                .map_or(true, |definition| definition.blocks_movement),
+            EdgeStructure::Door { is_open } => !is_open,
+        }
+    }
+
+    pub fn encloses_rooms(&self, all_items: &AllItems) -> bool {
+        match self {
+            EdgeStructure::Wall { structure_id } => all_items
+                .edge_structures
+                .get(structure_id)
+                .map_or(false, |definition| definition.encloses_rooms),
+            EdgeStructure::Door { .. } => true,
+        }
+    }
+
+    pub fn build_cost<'a>(&self, all_items: &'a AllItems) -> Option<&'a BuildCost> {
+        match self {
+            EdgeStructure::Wall { structure_id } => all_items
+                .edge_structures
+                .get(structure_id)
+                .map(|definition| &definition.build_cost),
+            EdgeStructure::Door { .. } => Some(&all_items.door.build_cost),
+        }
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone)]
 pub struct TileWalls {
-    pub north: bool,
-    pub east: bool,
-    pub south: bool,
-    pub west: bool,
+    pub north: Option<EdgeStructure>,
+    pub east: Option<EdgeStructure>,
+    pub south: Option<EdgeStructure>,
+    pub west: Option<EdgeStructure>,
 }
 
 impl TileWalls {
-    pub fn at(&self, direction: CardinalDirection) -> bool {
+    pub fn at(&self, direction: CardinalDirection) -> Option<EdgeStructure> {
         match direction {
             CardinalDirection::North => self.north,
             CardinalDirection::East => self.east,
@@ -36,7 +83,7 @@ impl TileWalls {
         }
     }
 
-    pub fn set_at(&mut self, direction: CardinalDirection, value: bool) {
+    pub fn set_at(&mut self, direction: CardinalDirection, value: Option<EdgeStructure>) {
         match direction {
             CardinalDirection::North => self.north = value,
             CardinalDirection::East => self.east = value,
@@ -44,4 +91,14 @@ impl TileWalls {
             CardinalDirection::West => self.west = value,
         }
     }
+
+    pub fn blocks_movement_at(&self, direction: CardinalDirection, all_items: &AllItems) -> bool {
+        self.at(direction)
+            .map_or(false, |structure| structure.blocks_movement(all_items))
+    }
+
+    pub fn encloses_rooms_at(&self, direction: CardinalDirection, all_items: &AllItems) -> bool {
+        self.at(direction)
+            .map_or(false, |structure| structure.encloses_rooms(all_items))
+    }
 }
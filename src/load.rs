@@ -1,9 +1,15 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use bevy_asset_loader::prelude::*;
 use bevy_kira_audio::AudioSource;
 
-use data::prelude::{AllItems, CropDefinition, CropId, PropDefinition, PropId};
+use data::prelude::{
+    AllItems, BuildCost, CropDefinition, CropId, DoorDefinition, EdgePostDefinition,
+    EdgeStructureDefinition, EdgeStructureId, PropDefinition, PropId, ResourceDefinition,
+    ResourceId, Season,
+};
 
 use crate::GameState;
 
@@ -50,6 +56,14 @@ pub struct DebugMeshes {
     pub tile: Handle<Mesh>,
     pub wall: Handle<Mesh>,
     pub torch: Handle<Mesh>,
+    pub shipping_bin: Handle<Mesh>,
+    pub tool: Handle<Mesh>,
+    pub roof: Handle<Mesh>,
+    pub door: Handle<Mesh>,
+    pub fence: Handle<Mesh>,
+    pub fence_post: Handle<Mesh>,
+    pub hedge: Handle<Mesh>,
+    pub resource: Handle<Mesh>,
     pub wall_segment_front: Handle<Mesh>,
     pub wall_segment_top: Handle<Mesh>,
     pub wall_segment_side: Handle<Mesh>,
@@ -66,6 +80,14 @@ impl FromWorld for DebugMeshes {
             tile: mesh_assets.add(Rectangle::new(1.0, 1.0).into()),
             wall: mesh_assets.add(Cuboid::new(1.0, 2.0, 0.1).into()),
             torch: mesh_assets.add(Cuboid::new(0.1, 0.3, 0.1).into()),
+            shipping_bin: mesh_assets.add(Cuboid::new(0.9, 0.6, 0.6).into()),
+            tool: mesh_assets.add(Cuboid::new(0.6, 0.08, 0.08).into()),
+            roof: mesh_assets.add(Cuboid::new(1.0, 0.1, 1.0).into()),
+            door: mesh_assets.add(Cuboid::new(0.9, 1.8, 0.08).into()),
+            fence: mesh_assets.add(Cuboid::new(1.0, 0.6, 0.05).into()),
+            fence_post: mesh_assets.add(Cuboid::new(0.12, 0.8, 0.12).into()),
+            hedge: mesh_assets.add(Cuboid::new(1.0, 1.2, 0.4).into()),
+            resource: mesh_assets.add(Cuboid::new(0.25, 0.25, 0.25).into()),
             wall_segment_front: mesh_assets.add(Rectangle::new(1.0, 2.0).into()),
             wall_segment_top: mesh_assets.add(Rectangle::new(1.0, 0.1).into()),
             wall_segment_side: mesh_assets.add(Rectangle::new(0.1, 2.0).into()),
@@ -77,9 +99,18 @@ impl FromWorld for DebugMeshes {
 pub struct DebugMaterials {
     pub grass: Handle<StandardMaterial>,
     pub tilled: Handle<StandardMaterial>,
+    pub weeds: Handle<StandardMaterial>,
     pub wall: Handle<StandardMaterial>,
+    pub shipping_bin: Handle<StandardMaterial>,
+    pub roof: Handle<StandardMaterial>,
+    pub roof_hidden: Handle<StandardMaterial>,
+    pub door: Handle<StandardMaterial>,
+    pub fence: Handle<StandardMaterial>,
+    pub hedge: Handle<StandardMaterial>,
     pub wall_hidden: Handle<StandardMaterial>,
+    pub wall_faded: Handle<StandardMaterial>,
     pub preview_ghost: Handle<StandardMaterial>,
+    pub out_of_reach_cursor: Handle<StandardMaterial>,
 }
 
 impl FromWorld for DebugMaterials {
@@ -88,6 +119,9 @@ impl FromWorld for DebugMaterials {
         let debug_textures = cell
             .get_resource::<DebugTexturesForMaterials>()
             .expect("Failed to get DebugTexturesForMaterials");
+        let sprite_assets = cell
+            .get_resource::<SpriteAssets>()
+            .expect("Failed to get SpriteAssets");
 
         let mut standard_materials = cell
             .get_resource_mut::<Assets<StandardMaterial>>()
@@ -104,22 +138,77 @@ impl FromWorld for DebugMaterials {
                 reflectance: 0.0,
                 ..default()
             }),
+            weeds: standard_materials.add(StandardMaterial {
+                base_color: Color::rgb(0.55, 0.85, 0.4),
+                base_color_texture: Some(debug_textures.tilled.clone()),
+                reflectance: 0.0,
+                ..default()
+            }),
             wall: standard_materials.add(StandardMaterial {
                 base_color: Color::rgb(0.8, 0.8, 0.8),
                 reflectance: 0.3,
                 perceptual_roughness: 0.7,
                 ..default()
             }),
+            shipping_bin: standard_materials.add(StandardMaterial {
+                base_color: Color::rgb(0.55, 0.35, 0.2),
+                reflectance: 0.1,
+                perceptual_roughness: 0.9,
+                ..default()
+            }),
+            roof: standard_materials.add(StandardMaterial {
+                base_color: Color::rgb(0.6, 0.25, 0.2),
+                reflectance: 0.2,
+                perceptual_roughness: 0.8,
+                ..default()
+            }),
+            roof_hidden: standard_materials.add(StandardMaterial {
+                base_color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+                alpha_mode: AlphaMode::Multiply,
+                ..default()
+            }),
+            door: standard_materials.add(StandardMaterial {
+                base_color: Color::rgb(0.45, 0.3, 0.15),
+                reflectance: 0.1,
+                perceptual_roughness: 0.9,
+                ..default()
+            }),
+            fence: standard_materials.add(StandardMaterial {
+                base_color: Color::rgb(0.6, 0.45, 0.3),
+                reflectance: 0.1,
+                perceptual_roughness: 0.9,
+                ..default()
+            }),
+            hedge: standard_materials.add(StandardMaterial {
+                base_color: Color::rgb(0.2, 0.45, 0.15),
+                reflectance: 0.05,
+                perceptual_roughness: 1.0,
+                ..default()
+            }),
             wall_hidden: standard_materials.add(StandardMaterial {
                 base_color: Color::rgba(0.0, 0.0, 0.0, 0.0),
                 alpha_mode: AlphaMode::Multiply,
                 ..default()
             }),
+            wall_faded: standard_materials.add(StandardMaterial {
+                base_color: Color::rgba(0.8, 0.8, 0.8, 0.25),
+                alpha_mode: AlphaMode::Blend,
+                reflectance: 0.3,
+                perceptual_roughness: 0.7,
+                ..default()
+            }),
             preview_ghost: standard_materials.add(StandardMaterial {
                 base_color: Color::rgba(0.0, 0.4, 0.0, 0.4),
                 alpha_mode: AlphaMode::Premultiplied,
                 ..default()
             }),
+            out_of_reach_cursor: standard_materials.add(StandardMaterial {
+                base_color: Color::rgba(1.0, 0.3, 0.3, 0.5),
+                base_color_texture: Some(sprite_assets.cursor.clone()),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
         }
     }
 }
@@ -160,6 +249,11 @@ fn process_data(world: &mut World) {
     let crops = AllItems {
         crops: parse_crops(&assets),
         props: parse_props(meshes, materials),
+        edge_structures: parse_edge_structures(meshes, materials),
+        resources: parse_resources(meshes, materials),
+        door: DoorDefinition {
+            build_cost: BuildCost::new(vec![(WOOD, 3)]),
+        },
     };
 
     world.insert_resource(crops);
@@ -176,7 +270,10 @@ fn parse_crops(
             id: CropId(0),
             name: String::from("Blue Debug Plant"),
             stages: 4,
-            growth_time_per_stage: 5,
+            growth_time_per_stage: Duration::from_secs(5),
+            sell_price: 35,
+            seed_price: 15,
+            seasons: vec![Season::Spring, Season::Summer],
             texture_atlas: assets.blue_debug_plant.clone(),
             harvested_sprite: assets.blue_debug_veggie.clone(),
         },
@@ -187,7 +284,10 @@ fn parse_crops(
             id: CropId(1),
             name: String::from("Red Debug Plant"),
             stages: 4,
-            growth_time_per_stage: 1,
+            growth_time_per_stage: Duration::from_secs(1),
+            sell_price: 10,
+            seed_price: 5,
+            seasons: vec![Season::Autumn],
             texture_atlas: assets.red_debug_plant.clone(),
             harvested_sprite: assets.red_debug_veggie.clone(),
         },
@@ -209,6 +309,117 @@ fn parse_props(
             name: String::from("Torch"),
             mesh: meshes.torch.clone(),
             material: materials.wall.clone(),
+            height: 0.3,
+            is_solid: false,
+            is_shipping_bin: false,
+            build_cost: BuildCost::new(vec![(WOOD, 1)]),
+        },
+    );
+    definitions.insert(
+        PropId(1),
+        PropDefinition {
+            id: PropId(1),
+            name: String::from("Shipping Bin"),
+            mesh: meshes.shipping_bin.clone(),
+            material: materials.shipping_bin.clone(),
+            height: 0.6,
+            is_solid: true,
+            is_shipping_bin: true,
+            build_cost: BuildCost::new(vec![(WOOD, 10)]),
+        },
+    );
+
+    definitions
+}
+
+fn parse_edge_structures(
+    meshes: &DebugMeshes,
+    materials: &DebugMaterials,
+) -> HashMap<EdgeStructureId, EdgeStructureDefinition> {
+    let mut definitions = HashMap::new();
+
+    definitions.insert(
+        EdgeStructureId(0),
+        EdgeStructureDefinition {
+            id: EdgeStructureId(0),
+            name: String::from("Stone Wall"),
+            mesh: meshes.wall.clone(),
+            material: materials.wall.clone(),
+            height: 2.0,
+            thickness: 0.1,
+            blocks_movement: true,
+            blocks_sight: true,
+            encloses_rooms: true,
+            post: None,
+            build_cost: BuildCost::new(vec![(STONE, 2)]),
+        },
+    );
+    definitions.insert(
+        EdgeStructureId(1),
+        EdgeStructureDefinition {
+            id: EdgeStructureId(1),
+            name: String::from("Wooden Fence"),
+            mesh: meshes.fence.clone(),
+            material: materials.fence.clone(),
+            height: 0.6,
+            thickness: 0.05,
+            blocks_movement: true,
+            blocks_sight: false,
+            encloses_rooms: false,
+            post: Some(EdgePostDefinition {
+                mesh: meshes.fence_post.clone(),
+                height: 0.8,
+            }),
+            build_cost: BuildCost::new(vec![(WOOD, 2)]),
+        },
+    );
+    definitions.insert(
+        EdgeStructureId(2),
+        EdgeStructureDefinition {
+            id: EdgeStructureId(2),
+            name: String::from("Hedge"),
+            mesh: meshes.hedge.clone(),
+            material: materials.hedge.clone(),
+            height: 1.2,
+            thickness: 0.4,
+            blocks_movement: true,
+            blocks_sight: true,
+            encloses_rooms: false,
+            post: None,
+            build_cost: BuildCost::new(vec![(WOOD, 1)]),
+        },
+    );
+
+    definitions
+}
+
+const WOOD: ResourceId = ResourceId(0);
+const STONE: ResourceId = ResourceId(1);
+
+fn parse_resources(
+    meshes: &DebugMeshes,
+    materials: &DebugMaterials,
+) -> HashMap<ResourceId, ResourceDefinition> {
+    let mut definitions = HashMap::new();
+
+    definitions.insert(
+        WOOD,
+        ResourceDefinition {
+            id: WOOD,
+            name: String::from("Wood"),
+            price: 2,
+            mesh: meshes.resource.clone(),
+            material: materials.fence.clone(),
+        },
+    );
+    definitions.insert(
+        STONE,
+        ResourceDefinition {
+            id: STONE,
+            name: String::from("Stone"),
+            price: 3,
+            mesh: meshes.resource.clone(),
+            material: materials.wall.clone(),
         },
     );
 
@@ -6,10 +6,95 @@ pub(crate) use {
     crate::load::*, crate::GameState,
 };
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+use bevy::math::Vec3;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum CardinalDirection {
     North,
     East,
     South,
     West,
 }
+
+impl CardinalDirection {
+    pub const ALL: [CardinalDirection; 4] = [
+        CardinalDirection::North,
+        CardinalDirection::East,
+        CardinalDirection::South,
+        CardinalDirection::West,
+    ];
+
+    /// North points towards negative z.
+    pub fn as_vec3(&self) -> Vec3 {
+        match self {
+            CardinalDirection::North => Vec3::NEG_Z,
+            CardinalDirection::East => Vec3::X,
+            CardinalDirection::South => Vec3::Z,
+            CardinalDirection::West => Vec3::NEG_X,
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            CardinalDirection::North => CardinalDirection::South,
+            CardinalDirection::East => CardinalDirection::West,
+            CardinalDirection::South => CardinalDirection::North,
+            CardinalDirection::West => CardinalDirection::East,
+        }
+    }
+
+    pub fn rotated_clockwise(&self) -> Self {
+        match self {
+            CardinalDirection::North => CardinalDirection::East,
+            CardinalDirection::East => CardinalDirection::South,
+            CardinalDirection::South => CardinalDirection::West,
+            CardinalDirection::West => CardinalDirection::North,
+        }
+    }
+
+    /// How many clockwise quarter turns it takes to get from North to this direction.
+    pub fn quarter_turns_from_north(&self) -> u32 {
+        match self {
+            CardinalDirection::North => 0,
+            CardinalDirection::East => 1,
+            CardinalDirection::South => 2,
+            CardinalDirection::West => 3,
+        }
+    }
+
+    /// Rotates the direction around the y axis, snapping to the closest quarter turn.
+    /// Positive yaw turns North towards West, just like [bevy::math::Quat::from_rotation_y].
+    pub fn rotated_by_yaw(&self, yaw: f32) -> Self {
+        let quarter_turns = (yaw / std::f32::consts::FRAC_PI_2).round() as i32;
+        let mut result = *self;
+        for _ in 0..quarter_turns.rem_euclid(4) {
+            result = match result {
+                CardinalDirection::North => CardinalDirection::West,
+                CardinalDirection::West => CardinalDirection::South,
+                CardinalDirection::South => CardinalDirection::East,
+                CardinalDirection::East => CardinalDirection::North,
+            };
+        }
+
+        result
+    }
+
+    /// The direction closest to the given vector on the xz plane, or None if it has no length there.
+    pub fn from_vec3(direction: Vec3) -> Option<Self> {
+        if direction.x == 0.0 && direction.z == 0.0 {
+            return None;
+        }
+
+        Some(if direction.x.abs() > direction.z.abs() {
+            if direction.x > 0.0 {
+                CardinalDirection::East
+            } else {
+                CardinalDirection::West
+            }
+        } else if direction.z > 0.0 {
+            CardinalDirection::South
+        } else {
+            CardinalDirection::North
+        })
+    }
+}